@@ -0,0 +1,280 @@
+use crate::types::ExclusionReason;
+use anyhow::Result;
+use git2::{ObjectType, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Path, PathBuf};
+
+/// Estado de um atributo em uma linha do `.gitattributes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AttrState {
+    /// `attr`
+    Set,
+    /// `-attr`
+    Unset,
+    /// `attr=valor`
+    Value(String),
+    /// `!attr`: volta a não especificado
+    Unspecified,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// Diretório do `.gitattributes` que definiu a regra.
+    dir: PathBuf,
+    /// Padrões sem `/` casam só o nome do arquivo, em qualquer nível abaixo de `dir`.
+    basename_only: bool,
+    matcher: GlobMatcher,
+    attrs: Vec<(String, AttrState)>,
+}
+
+/// Atributos definidos pelos arquivos `.gitattributes` de uma árvore.
+///
+/// Só o necessário para classificar arquivos gerados e vendorizados: padrões com glob,
+/// precedência do arquivo mais profundo e da linha mais abaixo, e a macro `binary`.
+#[derive(Debug, Default)]
+pub struct GitAttributes {
+    /// Em ordem de precedência crescente.
+    rules: Vec<Rule>,
+}
+
+impl GitAttributes {
+    /// Lê todos os `.gitattributes` de `tree` (a revisão analisada, não o diretório de trabalho).
+    pub fn from_tree(repo: &Repository, tree: &Tree) -> Result<Self> {
+        let mut files = Vec::new();
+
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.name() == Some(".gitattributes") && entry.kind() == Some(ObjectType::Blob) {
+                files.push((PathBuf::from(dir), entry.id()));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        // Arquivos mais profundos têm precedência, então entram por último
+        files.sort_by_key(|(dir, _)| dir.components().count());
+
+        let mut attributes = Self::default();
+        for (dir, oid) in files {
+            let blob = repo.find_blob(oid)?;
+            attributes.add_file(&dir, &String::from_utf8_lossy(blob.content()));
+        }
+
+        Ok(attributes)
+    }
+
+    /// Acrescenta as regras de um `.gitattributes` localizado em `dir`.
+    pub fn add_file(&mut self, dir: &Path, content: &str) {
+        self.rules
+            .extend(content.lines().filter_map(|line| parse_line(dir, line)));
+    }
+
+    /// Motivo para excluir `path` da análise, conforme `linguist-generated`,
+    /// `linguist-vendored` e `-diff`.
+    pub fn exclusion_reason(&self, path: &Path) -> Option<ExclusionReason> {
+        let is_true = |state: Option<AttrState>| match state {
+            Some(AttrState::Set) => true,
+            Some(AttrState::Value(value)) => value == "true" || value == "1",
+            _ => false,
+        };
+
+        if is_true(self.lookup(path, "linguist-generated")) {
+            Some(ExclusionReason::LinguistGenerated)
+        } else if is_true(self.lookup(path, "linguist-vendored")) {
+            Some(ExclusionReason::LinguistVendored)
+        } else if self.lookup(path, "diff") == Some(AttrState::Unset) {
+            Some(ExclusionReason::NoDiff)
+        } else {
+            None
+        }
+    }
+
+    fn lookup(&self, path: &Path, attr: &str) -> Option<AttrState> {
+        for rule in self.rules.iter().rev() {
+            let Some((_, state)) = rule.attrs.iter().rev().find(|(name, _)| name == attr) else {
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(&rule.dir) else {
+                continue;
+            };
+
+            let candidate = if rule.basename_only {
+                match relative.file_name() {
+                    Some(name) => Path::new(name),
+                    None => continue,
+                }
+            } else {
+                relative
+            };
+
+            if rule.matcher.is_match(candidate) {
+                return match state {
+                    AttrState::Unspecified => None,
+                    state => Some(state.clone()),
+                };
+            }
+        }
+
+        None
+    }
+}
+
+fn parse_line(dir: &Path, line: &str) -> Option<Rule> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let pattern = fields.next()?;
+
+    let mut attrs = Vec::new();
+    for field in fields {
+        if field == "binary" {
+            // Macro embutida: binary = -diff -merge -text
+            attrs.push(("diff".to_string(), AttrState::Unset));
+            continue;
+        }
+
+        let attr = if let Some(name) = field.strip_prefix('-') {
+            (name.to_string(), AttrState::Unset)
+        } else if let Some(name) = field.strip_prefix('!') {
+            (name.to_string(), AttrState::Unspecified)
+        } else if let Some((name, value)) = field.split_once('=') {
+            (name.to_string(), AttrState::Value(value.to_string()))
+        } else {
+            (field.to_string(), AttrState::Set)
+        };
+        attrs.push(attr);
+    }
+
+    // Padrões de diretório ("vendor/") não se aplicam a arquivos no gitattributes
+    if attrs.is_empty() || pattern.ends_with('/') {
+        return None;
+    }
+
+    let basename_only = !pattern.contains('/');
+    let glob = GlobBuilder::new(pattern.trim_start_matches('/'))
+        .literal_separator(true)
+        .build()
+        .ok()?;
+
+    Some(Rule {
+        dir: dir.to_path_buf(),
+        basename_only,
+        matcher: glob.compile_matcher(),
+        attrs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    fn attributes(content: &str) -> GitAttributes {
+        let mut attributes = GitAttributes::default();
+        attributes.add_file(Path::new(""), content);
+        attributes
+    }
+
+    fn reason(attributes: &GitAttributes, path: &str) -> Option<ExclusionReason> {
+        attributes.exclusion_reason(Path::new(path))
+    }
+
+    #[test]
+    fn test_linguist_attributes() {
+        let attrs = attributes(
+            "# comentário\n\
+             *.pb.go linguist-generated\n\
+             vendor/** linguist-vendored=true\n\
+             *.min.js -diff\n\
+             *.png binary\n",
+        );
+
+        assert_eq!(
+            reason(&attrs, "api/v1/service.pb.go"),
+            Some(ExclusionReason::LinguistGenerated)
+        );
+        assert_eq!(
+            reason(&attrs, "vendor/lib/a.go"),
+            Some(ExclusionReason::LinguistVendored)
+        );
+        assert_eq!(
+            reason(&attrs, "web/app.min.js"),
+            Some(ExclusionReason::NoDiff)
+        );
+        assert_eq!(
+            reason(&attrs, "img/logo.png"),
+            Some(ExclusionReason::NoDiff)
+        );
+        assert_eq!(reason(&attrs, "src/main.go"), None);
+        assert_eq!(reason(&attrs, "src/vendor/a.go"), None);
+    }
+
+    #[test]
+    fn test_later_lines_override_earlier() {
+        let attrs = attributes(
+            "*.pb.go linguist-generated\n\
+             keep.pb.go linguist-generated=false\n\
+             other.pb.go !linguist-generated\n",
+        );
+
+        assert_eq!(
+            reason(&attrs, "a.pb.go"),
+            Some(ExclusionReason::LinguistGenerated)
+        );
+        assert_eq!(reason(&attrs, "keep.pb.go"), None);
+        assert_eq!(reason(&attrs, "other.pb.go"), None);
+    }
+
+    #[test]
+    fn test_nested_gitattributes_scoped_to_directory() {
+        let mut attrs = attributes("*.js linguist-generated\n");
+        attrs.add_file(
+            Path::new("src"),
+            "*.js -linguist-generated\n/gen/*.ts linguist-generated\n",
+        );
+
+        assert_eq!(
+            reason(&attrs, "dist/app.js"),
+            Some(ExclusionReason::LinguistGenerated)
+        );
+        assert_eq!(reason(&attrs, "src/app.js"), None);
+        assert_eq!(
+            reason(&attrs, "src/gen/api.ts"),
+            Some(ExclusionReason::LinguistGenerated)
+        );
+        assert_eq!(reason(&attrs, "gen/api.ts"), None);
+    }
+
+    #[test]
+    fn test_from_tree_reads_committed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        let root_attrs = repo.blob(b"*.lock -diff\n").unwrap();
+        let nested_attrs = repo.blob(b"*.ts linguist-generated\n").unwrap();
+        let mut nested = repo.treebuilder(None).unwrap();
+        nested
+            .insert(".gitattributes", nested_attrs, 0o100644)
+            .unwrap();
+        let nested = nested.write().unwrap();
+        let mut root = repo.treebuilder(None).unwrap();
+        root.insert(".gitattributes", root_attrs, 0o100644).unwrap();
+        root.insert("gen", nested, 0o040000).unwrap();
+        let tree = repo.find_tree(root.write().unwrap()).unwrap();
+
+        let signature = Signature::now("Alice", "alice@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "attrs", &tree, &[])
+            .unwrap();
+
+        let attrs = GitAttributes::from_tree(&repo, &tree).unwrap();
+
+        assert_eq!(reason(&attrs, "Cargo.lock"), Some(ExclusionReason::NoDiff));
+        assert_eq!(
+            reason(&attrs, "gen/api.ts"),
+            Some(ExclusionReason::LinguistGenerated)
+        );
+        assert_eq!(reason(&attrs, "src/api.ts"), None);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::git_analyzer::CommitChanges;
+use anyhow::Result;
+use git2::Oid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Versão do formato em disco; mudar invalida caches antigos.
+const CACHE_VERSION: u32 = 6;
+
+/// Diretório, dentro do diretório do git, onde o cache é guardado.
+const CACHE_DIR: &str = "hotspot-analyzer";
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    fingerprint: String,
+    commits: HashMap<String, CommitChanges>,
+}
+
+/// Cache em disco das mudanças por commit, indexado pelo OID.
+///
+/// O conteúdo só é reaproveitado quando foi gerado com as mesmas opções de diff
+/// (`fingerprint`); caso contrário o cache começa vazio e é sobrescrito ao salvar.
+pub struct ChangeCache {
+    path: PathBuf,
+    fingerprint: String,
+    commits: HashMap<String, CommitChanges>,
+    dirty: bool,
+}
+
+impl ChangeCache {
+    pub fn open(git_dir: &Path, fingerprint: String) -> Self {
+        let path = git_dir.join(CACHE_DIR).join("cache.json");
+
+        // Cache ausente, corrompido ou de outra versão é simplesmente descartado
+        let commits = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<CacheFile>(&content).ok())
+            .filter(|file| file.version == CACHE_VERSION && file.fingerprint == fingerprint)
+            .map(|file| file.commits)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            fingerprint,
+            commits,
+            dirty: false,
+        }
+    }
+
+    pub fn get(&self, oid: Oid) -> Option<&CommitChanges> {
+        self.commits.get(&oid.to_string())
+    }
+
+    pub fn insert(&mut self, oid: Oid, changes: CommitChanges) {
+        self.commits.insert(oid.to_string(), changes);
+        self.dirty = true;
+    }
+
+    /// Grava o cache se houve mudanças, via arquivo temporário para não deixar um cache
+    /// pela metade se o processo for interrompido.
+    pub fn save(self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            fingerprint: self.fingerprint,
+            commits: self.commits,
+        };
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&file)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_changes() -> CommitChanges {
+        CommitChanges {
+            files: vec![(PathBuf::from("src/main.rs"), 10)],
+            renamed: vec![(PathBuf::from("old.rs"), PathBuf::from("new.rs"))],
+            added: vec![PathBuf::from("new.rs")],
+            binaries: vec![(PathBuf::from("logo.png"), -512)],
+            functions: vec![(PathBuf::from("src/main.rs"), "main".to_string(), 4)],
+        }
+    }
+
+    fn oid() -> Oid {
+        Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap()
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut cache = ChangeCache::open(temp_dir.path(), "opcoes".to_string());
+        assert!(cache.get(oid()).is_none());
+        cache.insert(oid(), sample_changes());
+        cache.save().unwrap();
+
+        let cache = ChangeCache::open(temp_dir.path(), "opcoes".to_string());
+        let changes = cache.get(oid()).unwrap();
+        assert_eq!(changes.files, sample_changes().files);
+        assert_eq!(changes.renamed, sample_changes().renamed);
+        assert_eq!(changes.added, sample_changes().added);
+        assert_eq!(changes.binaries, sample_changes().binaries);
+        assert_eq!(changes.functions, sample_changes().functions);
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_fingerprint() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut cache = ChangeCache::open(temp_dir.path(), "renames=50".to_string());
+        cache.insert(oid(), sample_changes());
+        cache.save().unwrap();
+
+        let cache = ChangeCache::open(temp_dir.path(), "renames=70".to_string());
+        assert!(cache.get(oid()).is_none());
+    }
+
+    #[test]
+    fn test_cache_ignores_corrupted_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join(CACHE_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cache.json"), "{ isso não é json").unwrap();
+
+        let mut cache = ChangeCache::open(temp_dir.path(), "opcoes".to_string());
+        assert!(cache.get(oid()).is_none());
+        cache.insert(oid(), sample_changes());
+        assert!(cache.save().is_ok());
+    }
+}
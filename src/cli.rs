@@ -1,4 +1,13 @@
+use crate::complexity::ComplexityMetric;
+use crate::filters::{AuthorFilter, PathFilter};
+use crate::git_analyzer::{
+    default_jobs, AnalysisOptions, DateField, Granularity, MassChangePolicy, MergePolicy,
+    DEFAULT_RENAME_THRESHOLD, DEFAULT_TREND_SAMPLES,
+};
+use crate::identity::AuthorKey;
+use anyhow::Result;
 use clap::Parser;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -8,15 +17,23 @@ pub struct Cli {
     #[arg(short, long, default_value = ".")]
     pub repo: PathBuf,
 
+    /// Início do período: data (2024-01-15, RFC 3339), relativa ("6 months ago", 2w) ou revisão (v1.2.0)
     #[arg(long)]
     pub since: Option<String>,
 
+    /// Fim do período, nos mesmos formatos de --since; uma data sem horário inclui o dia inteiro
     #[arg(long)]
     pub until: Option<String>,
 
+    /// Data do commit usada por --since/--until
+    #[arg(long, value_enum, default_value_t = DateField::Committer)]
+    pub date_field: DateField,
+
+    /// Analisa só os caminhos que casam com estes globs ou pathspecs (ex.: src, '*.rs', ':!vendor')
     #[arg(long, value_delimiter = ',')]
     pub include: Vec<String>,
 
+    /// Ignora os caminhos que casam com estes globs (ex.: test, '**/*.generated.ts')
     #[arg(long, value_delimiter = ',')]
     pub exclude: Vec<String>,
 
@@ -31,4 +48,147 @@ pub struct Cli {
 
     #[arg(long)]
     pub out: Option<PathBuf>,
+
+    /// Similaridade mínima (0-100) para seguir renomeações de arquivos
+    #[arg(long, default_value_t = DEFAULT_RENAME_THRESHOLD, value_parser = clap::value_parser!(u16).range(0..=100))]
+    pub rename_threshold: u16,
+
+    /// Não detecta renomeações: cada caminho é tratado como um arquivo independente
+    #[arg(long)]
+    pub no_renames: bool,
+
+    /// Como contar commits de merge
+    #[arg(long, value_enum, default_value_t = MergePolicy::Conflicts)]
+    pub merges: MergePolicy,
+
+    /// Arquivo de aliases de autores no formato .mailmap, aplicado além do .mailmap do repositório
+    #[arg(long)]
+    pub mailmap: Option<PathBuf>,
+
+    /// Identifica autores pelo nome ou pelo e-mail
+    #[arg(long, value_enum, default_value_t = AuthorKey::Name)]
+    pub author_key: AuthorKey,
+
+    /// Conta só commits de autores cujo nome ou e-mail casa com esta regex (pode repetir)
+    #[arg(long, value_name = "REGEX")]
+    pub include_author: Vec<String>,
+
+    /// Ignora commits de autores cujo nome ou e-mail casa com esta regex (pode repetir)
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_author: Vec<String>,
+
+    /// Ignora commits de bots conhecidos (dependabot[bot], Renovate, github-actions, *-bot...)
+    #[arg(long)]
+    pub exclude_bots: bool,
+
+    /// Revisão ou intervalo a analisar (ex.: v1.2.0, feature, main..feature); padrão: HEAD
+    #[arg(long)]
+    pub rev: Vec<String>,
+
+    /// Intervalo de commits no formato A..B ou A...B (ex.: v1.2.0..HEAD)
+    #[arg(long, value_parser = parse_range)]
+    pub range: Vec<String>,
+
+    /// Percorre todos os ramos locais
+    #[arg(long)]
+    pub all: bool,
+
+    /// Número de threads para calcular diffs (padrão: uma por CPU)
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Ignora o cache de diffs em .git/hotspot-analyzer/ e não o atualiza
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Não exclui arquivos marcados com linguist-generated, linguist-vendored ou -diff no .gitattributes
+    #[arg(long)]
+    pub no_gitattributes: bool,
+
+    /// Mantém lockfiles, arquivos minificados e com cabeçalho de código gerado (@generated, DO NOT EDIT)
+    #[arg(long)]
+    pub include_generated: bool,
+
+    /// Ignora diferenças de espaço em branco ao contar linhas alteradas (como git diff -w)
+    #[arg(short = 'w', long)]
+    pub ignore_whitespace: bool,
+
+    /// Ignora mudanças na quantidade de espaço em branco (como git diff -b)
+    #[arg(short = 'b', long)]
+    pub ignore_whitespace_change: bool,
+
+    /// Não conta os commits listados neste arquivo (padrão, sem valor: .git-blame-ignore-revs)
+    #[arg(long, num_args = 0..=1, default_missing_value = ".git-blame-ignore-revs")]
+    pub ignore_revs_file: Option<PathBuf>,
+
+    /// Trata como mudança em massa os commits que alteram mais que N arquivos
+    #[arg(long, value_name = "N")]
+    pub max_files_per_commit: Option<usize>,
+
+    /// Trata como mudança em massa os commits que alteram mais que N linhas
+    #[arg(long, value_name = "N")]
+    pub max_lines_per_commit: Option<usize>,
+
+    /// O que fazer com commits de mudança em massa
+    #[arg(long, value_enum, default_value_t = MassChangePolicy::Exclude)]
+    pub mass_changes: MassChangePolicy,
+
+    /// Inclui arquivos que não existem mais na revisão analisada
+    #[arg(long)]
+    pub include_deleted: bool,
+
+    /// Métrica usada na coluna de complexidade
+    #[arg(long, value_enum, default_value_t = ComplexityMetric::Cyclomatic)]
+    pub complexity_metric: ComplexityMetric,
+
+    /// Classifica arquivos inteiros ou cada função (`arquivo::função`)
+    #[arg(long, value_enum, default_value_t = Granularity::File)]
+    pub granularity: Granularity,
+
+    /// Amostra a complexidade dos hotspots em N commits espaçados por igual no histórico (0 desativa)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_TREND_SAMPLES)]
+    pub trend_samples: usize,
+}
+
+impl Cli {
+    pub fn analysis_options(&self) -> Result<AnalysisOptions> {
+        Ok(AnalysisOptions {
+            since: self.since.clone(),
+            until: self.until.clone(),
+            date_field: self.date_field,
+            rename_threshold: (!self.no_renames).then_some(self.rename_threshold),
+            merge_policy: self.merges,
+            alias_file: self.mailmap.clone(),
+            author_key: self.author_key,
+            author_filter: AuthorFilter::new(
+                &self.include_author,
+                &self.exclude_author,
+                self.exclude_bots,
+            )?,
+            revisions: self.rev.iter().chain(&self.range).cloned().collect(),
+            all_branches: self.all,
+            jobs: self.jobs.map_or_else(default_jobs, NonZeroUsize::get),
+            use_cache: !self.no_cache,
+            path_filter: PathFilter::new(&self.include, &self.exclude)?,
+            use_gitattributes: !self.no_gitattributes,
+            detect_generated: !self.include_generated,
+            ignore_whitespace: self.ignore_whitespace,
+            ignore_whitespace_change: self.ignore_whitespace_change,
+            ignore_revs_file: self.ignore_revs_file.clone(),
+            max_files_per_commit: self.max_files_per_commit,
+            max_lines_per_commit: self.max_lines_per_commit,
+            mass_change_policy: self.mass_changes,
+            include_deleted: self.include_deleted,
+            granularity: self.granularity,
+            trend_samples: self.trend_samples,
+        })
+    }
+}
+
+fn parse_range(value: &str) -> Result<String, String> {
+    if value.contains("..") {
+        Ok(value.to_string())
+    } else {
+        Err("intervalo deve estar no formato A..B ou A...B".to_string())
+    }
 }
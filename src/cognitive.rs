@@ -0,0 +1,426 @@
+use crate::cyclomatic::is_ternary;
+use crate::syntax::{Language, SourceCode, Token, TokenKind, ANONYMOUS};
+
+/// Complexidade cognitiva de cada função, na ordem de `code.functions`.
+///
+/// Segue as regras da complexidade cognitiva da SonarSource: cada quebra no fluxo linear
+/// (condicional, laço, `switch`/`match`, `catch`, ternário, salto para rótulo, recursão,
+/// sequência de operadores lógicos) soma 1, e as estruturas aninhadas somam também o nível
+/// de aninhamento em que estão. `else` e `else if` somam 1, sem o aninhamento. Funções
+/// aninhadas (inclusive closures e funções anônimas) começam no aninhamento em que foram
+/// definidas, mais 1.
+pub fn function_complexities(code: &SourceCode) -> Vec<usize> {
+    // Aninhamento inicial de cada função. As funções aparecem depois da que as contém, então
+    // o valor já está calculado quando chega a vez delas.
+    let mut nesting = vec![0; code.functions.len()];
+    (0..code.functions.len())
+        .map(|index| {
+            let function = &code.functions[index];
+            let name = function.name.rsplit("::").next().unwrap_or(ANONYMOUS);
+            let name = (name != ANONYMOUS).then_some(name);
+            complexity(
+                code,
+                function.tokens.clone(),
+                Some(index),
+                name,
+                &mut nesting,
+            )
+        })
+        .collect()
+}
+
+/// Complexidade cognitiva do arquivo: a soma das funções, mais a do código fora de funções
+/// nas linguagens que o admitem.
+pub fn file_complexity(code: &SourceCode) -> usize {
+    let functions: usize = function_complexities(code).iter().sum();
+
+    let top_level = if code.language.has_top_level_code() {
+        complexity(code, code.top_level_tokens(), None, None, &mut [])
+    } else {
+        0
+    };
+
+    functions + top_level
+}
+
+/// Estruturas que somam 1 mais o aninhamento e aumentam o aninhamento do bloco que abrem.
+fn nesting_structures(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Rust => &["if", "match", "for", "while", "loop"],
+        Language::Python => &["if", "for", "while", "except", "match"],
+        Language::JavaScript | Language::Java | Language::C => {
+            &["if", "switch", "for", "while", "do", "catch"]
+        }
+        Language::Go => &["if", "switch", "select", "for"],
+    }
+}
+
+fn is_logical_operator(token: &Token) -> bool {
+    token.kind != TokenKind::Str && ["&&", "||", "and", "or"].contains(&token.text)
+}
+
+/// Um bloco entre chaves aberto dentro da função.
+struct Block {
+    /// Aberto por uma estrutura de controle (e não por um bloco solto, literal etc.).
+    nests: bool,
+    /// Corpo de um `do ... while`.
+    is_do: bool,
+}
+
+/// Complexidade cognitiva da função `owner` chamada `name` (ou do código fora de funções,
+/// sem nome), dentre os tokens `indices`.
+///
+/// `nesting` guarda o aninhamento inicial de cada função: o de `owner` é lido dele, e o das
+/// funções definidas diretamente em `owner` é gravado nele.
+fn complexity(
+    code: &SourceCode,
+    indices: impl Iterator<Item = usize>,
+    owner: Option<usize>,
+    name: Option<&str>,
+    nesting: &mut [usize],
+) -> usize {
+    let tokens = &code.tokens;
+    let python = code.language == Language::Python;
+    let structures = nesting_structures(code.language);
+    let base_nesting = owner.map_or(0, |owner| nesting[owner]);
+
+    let mut total = 0;
+    // Linguagens com chaves: blocos abertos e a estrutura que abre o próximo bloco
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut pending: Option<&str> = None;
+    let mut after_do_block = false;
+    // Python: indentação das estruturas de controle abertas
+    let mut open_structures: Vec<usize> = Vec::new();
+    // Python: profundidade de parênteses das compreensões e dos lambdas abertos, e dos lambdas
+    // cujo corpo (depois do `:`) ainda não começou
+    let mut comprehensions: Vec<usize> = Vec::new();
+    let mut lambdas: Vec<usize> = Vec::new();
+    let mut lambda_headers: Vec<usize> = Vec::new();
+    let mut paren_depth = 0usize;
+    let mut last_logical: Option<&str> = None;
+    let mut name_seen = false;
+    let mut prev: Option<usize> = None;
+
+    for i in indices {
+        if code.owners[i] != owner {
+            // Início de uma função aninhada: herda o aninhamento do ponto onde está
+            if let Some(inner) = code.owners[i].filter(|&f| code.functions[f].tokens.start == i) {
+                let local = if python {
+                    let indent = code.indentation_at(i);
+                    open_structures
+                        .iter()
+                        .filter(|&&open| open < indent)
+                        .count()
+                } else {
+                    blocks.iter().filter(|block| block.nests).count()
+                };
+                nesting[inner] = base_nesting + local + 1;
+            }
+            continue;
+        }
+
+        let token = &tokens[i];
+        let prev_token = prev.map(|p| &tokens[p]);
+        prev = Some(i);
+
+        let starts_line = paren_depth == 0 && prev_token.is_none_or(|p| p.line != token.line);
+        if starts_line && !prev_token.is_some_and(is_logical_operator) {
+            last_logical = None;
+        }
+        if python && starts_line {
+            let indent = code.indentation_at(i);
+            while open_structures.last().is_some_and(|&open| open >= indent) {
+                open_structures.pop();
+            }
+            lambdas.clear();
+            lambda_headers.clear();
+        }
+
+        let nesting = base_nesting
+            + if python {
+                open_structures.len() + lambdas.len()
+            } else {
+                blocks.iter().filter(|block| block.nests).count()
+            };
+
+        if token.kind == TokenKind::Str || token.kind == TokenKind::Number {
+            continue;
+        }
+        let text = token.text;
+        let next = tokens.get(i + 1);
+
+        if token.kind == TokenKind::Word && Some(text) == name {
+            if name_seen && next.is_some_and(|t| t.is("(")) {
+                total += 1;
+            }
+            name_seen = true;
+            continue;
+        }
+
+        match text {
+            "(" | "[" => paren_depth += 1,
+            "{" if python => paren_depth += 1,
+            ")" | "]" | "}" if python => {
+                paren_depth = paren_depth.saturating_sub(1);
+                comprehensions.retain(|&depth| depth <= paren_depth);
+                lambdas.retain(|&depth| depth <= paren_depth);
+                lambda_headers.retain(|&depth| depth <= paren_depth);
+            }
+            ")" | "]" => paren_depth = paren_depth.saturating_sub(1),
+            // Lambdas não somam, mas aumentam o aninhamento do seu corpo
+            "lambda" if python => lambda_headers.push(paren_depth),
+            ":" if python && lambda_headers.last() == Some(&paren_depth) => {
+                lambda_headers.pop();
+                lambdas.push(paren_depth);
+            }
+            // Compreensões não somam nem aumentam o aninhamento
+            "for" if python && paren_depth > 0 => comprehensions.push(paren_depth),
+            "if" if python && comprehensions.last() == Some(&paren_depth) => {}
+            "{" => {
+                let opener = if paren_depth == 0 {
+                    pending.take()
+                } else {
+                    None
+                };
+                blocks.push(Block {
+                    nests: opener.is_some(),
+                    is_do: opener == Some("do"),
+                });
+                last_logical = None;
+            }
+            "}" => {
+                after_do_block = blocks.pop().is_some_and(|block| block.is_do);
+                last_logical = None;
+                continue;
+            }
+            ";" | "," => {
+                if paren_depth == 0 {
+                    pending = None;
+                }
+                lambdas.retain(|&depth| depth < paren_depth);
+                last_logical = None;
+            }
+            // Em Python, `else` no meio da linha é parte de um ternário, já contado no `if`
+            "else" | "elif" if python && !starts_line => {}
+            "else" | "elif" if python => {
+                total += 1;
+                open_structures.push(code.indentation_at(i));
+            }
+            "else" => {
+                total += 1;
+                pending = Some("else");
+            }
+            "if" if prev_token.is_some_and(|p| p.is("else")) && !python => {
+                pending = Some("if");
+            }
+            "while" if after_do_block => {}
+            "match" if python && !starts_line => {}
+            _ if token.kind == TokenKind::Word && structures.contains(&text) => {
+                total += 1 + nesting;
+                if python {
+                    if starts_line {
+                        open_structures.push(code.indentation_at(i));
+                    }
+                } else {
+                    pending = Some(text);
+                }
+            }
+            "?" if is_ternary(code, i) => total += 1 + nesting,
+            "goto" => total += 1,
+            "break" | "continue" => {
+                let labeled = next.is_some_and(|next| {
+                    next.line == token.line
+                        && if code.language == Language::Rust {
+                            next.is("'")
+                        } else {
+                            next.kind == TokenKind::Word
+                        }
+                });
+                if labeled {
+                    total += 1;
+                }
+            }
+            _ if is_logical_operator(token) => {
+                if last_logical != Some(text) {
+                    total += 1;
+                }
+                last_logical = Some(text);
+            }
+            _ => {}
+        }
+
+        after_do_block = false;
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complexities(language: Language, source: &str) -> (Vec<usize>, usize) {
+        let code = SourceCode::parse(language, source);
+        (function_complexities(&code), file_complexity(&code))
+    }
+
+    #[test]
+    fn test_nesting_is_penalized() {
+        // Mesma complexidade ciclomática, aninhamento diferente
+        let flat = "fn f(a: bool, b: bool, c: bool) {\n\
+                    if a { x(); }\n    if b { y(); }\n    if c { z(); }\n}\n";
+        let nested = "fn f(a: bool, b: bool, c: bool) {\n\
+                      if a {\n        if b {\n            if c { z(); }\n        }\n    }\n}\n";
+
+        assert_eq!(complexities(Language::Rust, flat), (vec![3], 3));
+        // 1 + (1 + 1) + (1 + 2)
+        assert_eq!(complexities(Language::Rust, nested), (vec![6], 6));
+    }
+
+    #[test]
+    fn test_rust_complexity() {
+        let source = r#"
+fn sum_of_primes(max: u32) -> u32 {
+    let mut total = 0;
+    'outer: for i in 1..=max {
+        for j in 2..i {
+            if i % j == 0 {
+                continue 'outer;
+            }
+        }
+        total += i;
+    }
+    total
+}
+
+fn kind(x: Option<u32>, a: bool, b: bool, c: bool) -> &'static str {
+    if a && b && c || a {
+        "a"
+    } else if let Some(_) = x {
+        "b"
+    } else {
+        match x {
+            Some(_) => "c",
+            None => "d",
+        }
+    }
+}
+"#;
+        // sum_of_primes: for (1) + for (2) + if (3) + continue 'outer (1)
+        // kind: if (1) + && e || (2) + else if (1) + else (1) + match (2)
+        assert_eq!(complexities(Language::Rust, source), (vec![7, 7], 14));
+    }
+
+    #[test]
+    fn test_python_complexity() {
+        let source = "\
+def f(items):
+    for item in items:
+        if item > 0 and item < 10:
+            pass
+        elif item == 0:
+            pass
+        else:
+            return [x for x in items if x]
+    try:
+        g()
+    except ValueError:
+        pass
+    return 1 if items else 0
+
+def fact(n):
+    return 1 if n <= 1 else n * fact(n - 1)
+";
+        // f: for (1) + if (2) + and (1) + elif (1) + else (1) + except (1) + ternário (1)
+        // fact: ternário (1) + recursão (1)
+        assert_eq!(complexities(Language::Python, source), (vec![8, 2], 10));
+    }
+
+    #[test]
+    fn test_javascript_and_java_complexity() {
+        let js = "\
+function f(a, b) {
+  switch (a) {
+    case 1:
+      return b ? 1 : 2;
+    default:
+      do { a--; } while (a > 0 && b);
+  }
+  try { g(); } catch (e) { if (e) { throw e; } }
+}
+";
+        // switch (1) + ternário (2) + do (2) + && (1) + catch (1) + if (2)
+        assert_eq!(complexities(Language::JavaScript, js), (vec![9], 9));
+
+        let java = "\
+class A {
+  void f(int[] xs) {
+    for (int i = 0; i < xs.length; i++) {
+      if (xs[i] > 0 || xs[i] < -10) { continue; }
+    }
+  }
+}
+";
+        // for (1) + if (2) + || (1)
+        assert_eq!(complexities(Language::Java, java), (vec![4], 4));
+    }
+
+    #[test]
+    fn test_go_complexity() {
+        let source = "\
+func f(ch chan int) {
+\tfor {
+\t\tselect {
+\t\tcase v := <-ch:
+\t\t\tif v > 0 {
+\t\t\t\tgoto done
+\t\t\t}
+\t\t}
+\t}
+done:
+}
+";
+        // for (1) + select (2) + if (3) + goto (1)
+        assert_eq!(complexities(Language::Go, source), (vec![7], 7));
+    }
+
+    #[test]
+    fn test_nested_functions_inherit_enclosing_nesting() {
+        let source = "\
+function outer(a) {
+  if (a) {
+    function inner(b) { if (b) {} }
+    a.forEach((c) => { if (c) {} });
+  }
+}
+";
+        // outer: if (1); inner e a arrow: if (1 + 2), dentro do if de outer e de outra função
+        assert_eq!(
+            complexities(Language::JavaScript, source),
+            (vec![1, 3, 3], 7)
+        );
+
+        let source = "\
+def outer(items):
+    for item in items:
+        def inner(x):
+            if x:
+                return 0
+        key = lambda v: 1 if v else 0
+";
+        // outer: for (1) + ternário no lambda (1 + 2); inner: if (1 + 2)
+        assert_eq!(complexities(Language::Python, source), (vec![4, 3], 7));
+    }
+
+    #[test]
+    fn test_comprehensions_do_not_nest() {
+        let source = "\
+def f(rows):
+    if rows:
+        return [[c for c in r if c] for r in rows if r]
+    return {k: (v if v else 0) for k, v in rows}
+";
+        // if (1) + ternário dentro da compreensão (1); os `for` e `if` das compreensões não somam
+        assert_eq!(complexities(Language::Python, source), (vec![2], 2));
+    }
+}
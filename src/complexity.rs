@@ -1,82 +1,572 @@
-use anyhow::Result;
+use crate::cognitive;
+use crate::cyclomatic;
+use crate::halstead;
+use crate::syntax::{Language, SourceCode};
+use crate::types::{FileMetrics, Halstead};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use git2::{ObjectType, Oid, Repository};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tokei::{Config, Languages};
+use tokei::{Config, LanguageType};
 
-pub fn analyze_file_complexity(path: &Path) -> Result<f64> {
-    if !path.exists() || !path.is_file() {
-        return Ok(0.0);
+/// Métrica usada como complexidade de cada arquivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ComplexityMetric {
+    /// Linhas de código (sem comentários nem linhas em branco), contadas pela tokei
+    Lines,
+    /// Complexidade ciclomática: soma, por função, de 1 mais os pontos de decisão; arquivos
+    /// de linguagens sem análise sintática usam a indentação
+    #[default]
+    Cyclomatic,
+    /// Complexidade cognitiva: como a ciclomática, mas penaliza o aninhamento; arquivos de
+    /// linguagens sem análise sintática usam a indentação
+    Cognitive,
+    /// Soma da indentação lógica das linhas, para qualquer arquivo de texto
+    Indentation,
+    /// Volume de Halstead; arquivos sem análise sintática usam a indentação
+    HalsteadVolume,
+    /// Dificuldade de Halstead; arquivos sem análise sintática usam a indentação
+    HalsteadDifficulty,
+    /// Esforço de Halstead; arquivos sem análise sintática usam a indentação
+    HalsteadEffort,
+    /// 100 menos o índice de manutenibilidade, para que arquivos difíceis de manter tenham
+    /// complexidade maior; arquivos sem análise sintática usam a indentação
+    Maintainability,
+}
+
+/// Complexidade calculada para um arquivo.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FileComplexity {
+    /// Valor da métrica escolhida, usado no score.
+    pub value: f64,
+    /// Maior indentação lógica, quando a complexidade veio da indentação.
+    pub max_indentation: Option<usize>,
+    /// Métricas de Halstead, para linguagens com análise sintática.
+    pub halstead: Option<Halstead>,
+    /// Índice de manutenibilidade, para linguagens com análise sintática.
+    pub maintainability_index: Option<f64>,
+    /// O valor veio da indentação porque a métrica pedida não se aplica ao arquivo (linguagem
+    /// sem análise sintática); ele não é comparável com os valores da métrica.
+    pub fallback: bool,
+}
+
+impl FileComplexity {
+    fn new(value: f64) -> Self {
+        Self {
+            value,
+            ..Self::default()
+        }
     }
+}
 
-    let mut languages = Languages::new();
-    let config = Config::default();
+impl From<IndentationComplexity> for FileComplexity {
+    fn from(indentation: IndentationComplexity) -> Self {
+        Self {
+            value: indentation.total as f64,
+            max_indentation: Some(indentation.max),
+            ..Self::default()
+        }
+    }
+}
 
-    let paths = vec![path.to_path_buf()];
-    languages.get_statistics(&paths, &[], &config);
+/// Métricas calculadas a partir da análise sintática de um arquivo.
+struct SyntaxMetrics {
+    cyclomatic: usize,
+    cognitive: usize,
+    halstead: Halstead,
+    maintainability_index: f64,
+}
 
-    let mut total_code = 0;
+impl SyntaxMetrics {
+    fn compute(language: Language, source: &str) -> Self {
+        let code = SourceCode::parse(language, source);
+        let cyclomatic = cyclomatic::file_complexity(&code);
+        let halstead = halstead::measure(&code);
 
-    for (_, language) in languages {
-        total_code += language.code;
+        Self {
+            cyclomatic,
+            cognitive: cognitive::file_complexity(&code),
+            maintainability_index: halstead::maintainability_index(
+                &halstead,
+                cyclomatic,
+                halstead::lines_of_code(&code),
+            ),
+            halstead,
+        }
     }
+}
+
+/// Complexidade de um arquivo, a partir do seu conteúdo. A linguagem é deduzida pelo nome do
+/// arquivo; com `lines`, arquivos de linguagem desconhecida têm complexidade 0, e arquivos
+/// binários têm complexidade 0 com qualquer métrica. Halstead e o índice de manutenibilidade
+/// são calculados para toda linguagem com análise sintática, qualquer que seja a métrica.
+pub fn analyze_content_complexity(
+    path: &Path,
+    content: &[u8],
+    metric: ComplexityMetric,
+) -> FileComplexity {
+    let syntax = Language::from_path(path)
+        .map(|language| SyntaxMetrics::compute(language, &String::from_utf8_lossy(content)));
 
-    Ok(total_code as f64)
+    let mut complexity = match (metric, &syntax) {
+        (ComplexityMetric::Lines, _) => FileComplexity::new(count_code_lines(path, content)),
+        (ComplexityMetric::Indentation, _) => indentation_of_content(content),
+        (_, None) => FileComplexity {
+            fallback: true,
+            ..indentation_of_content(content)
+        },
+        (ComplexityMetric::Cyclomatic, Some(syntax)) => {
+            FileComplexity::new(syntax.cyclomatic as f64)
+        }
+        (ComplexityMetric::Cognitive, Some(syntax)) => FileComplexity::new(syntax.cognitive as f64),
+        (ComplexityMetric::HalsteadVolume, Some(syntax)) => {
+            FileComplexity::new(syntax.halstead.volume)
+        }
+        (ComplexityMetric::HalsteadDifficulty, Some(syntax)) => {
+            FileComplexity::new(syntax.halstead.difficulty)
+        }
+        (ComplexityMetric::HalsteadEffort, Some(syntax)) => {
+            FileComplexity::new(syntax.halstead.effort)
+        }
+        (ComplexityMetric::Maintainability, Some(syntax)) => {
+            FileComplexity::new(100.0 - syntax.maintainability_index)
+        }
+    };
+
+    if let Some(syntax) = syntax {
+        complexity.halstead = Some(syntax.halstead);
+        complexity.maintainability_index = Some(syntax.maintainability_index);
+    }
+
+    complexity
+}
+
+/// Complexidade de cada função de um arquivo, pelo nome qualificado (`Tipo::metodo`).
+///
+/// Cada função conta só o próprio corpo, sem as funções aninhadas nela; funções com o mesmo
+/// nome (sobrecargas, funções anônimas) têm os valores somados. Com `lines`, conta as linhas
+/// com código, e com `indentation`, a indentação das linhas da função. Arquivos de
+/// linguagens sem análise sintática não têm funções.
+pub fn analyze_content_function_complexity(
+    path: &Path,
+    content: &[u8],
+    metric: ComplexityMetric,
+) -> HashMap<String, f64> {
+    let Some(language) = Language::from_path(path) else {
+        return HashMap::new();
+    };
+    let source = String::from_utf8_lossy(content);
+    let code = SourceCode::parse(language, &source);
+    let source_lines: Vec<&str> = source.lines().collect();
+
+    let cyclomatic = cyclomatic::function_complexities(&code);
+    let cognitive = if metric == ComplexityMetric::Cognitive {
+        cognitive::function_complexities(&code)
+    } else {
+        Vec::new()
+    };
+
+    let mut complexity = HashMap::new();
+
+    for (index, function) in code.functions.iter().enumerate() {
+        let tokens: Vec<_> = code.own_tokens(index).map(|i| &code.tokens[i]).collect();
+        let halstead = halstead::measure_tokens(language, tokens.iter().copied());
+        let lines = halstead::lines_of_tokens(tokens);
+
+        let value = match metric {
+            ComplexityMetric::Lines => lines as f64,
+            ComplexityMetric::Cyclomatic => cyclomatic[index] as f64,
+            ComplexityMetric::Cognitive => cognitive[index] as f64,
+            ComplexityMetric::Indentation => {
+                let first = function.start_line.saturating_sub(1);
+                let last = function.end_line.min(source_lines.len());
+                let text = source_lines[first.min(last)..last].join("\n");
+                indentation_complexity(&text).total as f64
+            }
+            ComplexityMetric::HalsteadVolume => halstead.volume,
+            ComplexityMetric::HalsteadDifficulty => halstead.difficulty,
+            ComplexityMetric::HalsteadEffort => halstead.effort,
+            ComplexityMetric::Maintainability => {
+                100.0 - halstead::maintainability_index(&halstead, cyclomatic[index], lines)
+            }
+        };
+
+        *complexity.entry(function.name.clone()).or_insert(0.0) += value;
+    }
+
+    complexity
+}
+
+fn indentation_of_content(content: &[u8]) -> FileComplexity {
+    match std::str::from_utf8(content) {
+        Ok(text) => indentation_complexity(text).into(),
+        Err(_) => FileComplexity::default(),
+    }
+}
+
+/// Indentação lógica de um texto: a soma e o máximo, sobre as linhas não vazias, do nível de
+/// indentação de cada linha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IndentationComplexity {
+    pub total: usize,
+    pub max: usize,
+}
+
+/// Unidade de indentação assumida quando o texto não tem linhas indentadas com espaços.
+const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// Calcula a indentação lógica sem depender da linguagem: cada tabulação é um nível, e os
+/// espaços são divididos pela menor indentação com espaços do arquivo (entre 2 e 8), de modo
+/// que YAML indentado com 2 espaços e Python com 4 fiquem na mesma escala.
+pub fn indentation_complexity(text: &str) -> IndentationComplexity {
+    let lines: Vec<(usize, usize)> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let indent: Vec<char> = line
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect();
+            let tabs = indent.iter().filter(|c| **c == '\t').count();
+            (tabs, indent.len() - tabs)
+        })
+        .collect();
+
+    let width = lines
+        .iter()
+        .map(|&(_, spaces)| spaces)
+        .filter(|&spaces| spaces > 0)
+        .min()
+        .map_or(DEFAULT_INDENT_WIDTH, |spaces| spaces.clamp(2, 8));
+
+    lines
+        .iter()
+        .map(|&(tabs, spaces)| tabs + spaces / width)
+        .fold(IndentationComplexity::default(), |acc, level| {
+            IndentationComplexity {
+                total: acc.total + level,
+                max: acc.max.max(level),
+            }
+        })
+}
+
+/// Completa as métricas dos arquivos com os detalhes da complexidade calculada.
+pub fn annotate(metrics: &mut [FileMetrics], complexity: &HashMap<PathBuf, FileComplexity>) {
+    for file in metrics {
+        if let Some(details) = complexity.get(&file.path) {
+            file.max_indentation = details.max_indentation;
+            file.halstead = details.halstead;
+            file.maintainability_index = details.maintainability_index;
+        }
+    }
+}
+
+fn count_code_lines(path: &Path, content: &[u8]) -> f64 {
+    let config = Config::default();
+
+    let Some(language) = LanguageType::from_path(path, &config) else {
+        return 0.0;
+    };
+
+    language.parse_from_slice(content, &config).summarise().code as f64
+}
+
+/// Complexidade dos arquivos `paths` (relativos à raiz) como estão no commit `revision`,
+/// lida direto dos blobs do git: não depende do diretório de trabalho e funciona em
+/// repositórios bare. Arquivos que não existem na revisão ficam fora do mapa.
+pub fn analyze_revision(
+    repo_path: &Path,
+    revision: Oid,
+    paths: &[PathBuf],
+    metric: ComplexityMetric,
+) -> Result<HashMap<PathBuf, FileComplexity>> {
+    analyze_revision_blobs(repo_path, revision, paths, |path, content| {
+        analyze_content_complexity(path, content, metric)
+    })
 }
 
-pub fn analyze_multiple_files(
+/// Complexidade de cada função dos arquivos `paths` no commit `revision`, lida dos blobs
+/// como em [`analyze_revision`].
+pub fn analyze_revision_functions(
+    repo_path: &Path,
+    revision: Oid,
     paths: &[PathBuf],
+    metric: ComplexityMetric,
+) -> Result<HashMap<PathBuf, HashMap<String, f64>>> {
+    analyze_revision_blobs(repo_path, revision, paths, |path, content| {
+        analyze_content_function_complexity(path, content, metric)
+    })
+}
+
+/// Aplica `analyze` ao conteúdo de cada arquivo de `paths` que existe no commit `revision`.
+fn analyze_revision_blobs<T>(
     repo_path: &Path,
-) -> HashMap<PathBuf, f64> {
+    revision: Oid,
+    paths: &[PathBuf],
+    analyze: impl Fn(&Path, &[u8]) -> T,
+) -> Result<HashMap<PathBuf, T>> {
+    let repo = Repository::open(repo_path).context("Falha ao abrir repositório")?;
+    let tree = repo.find_commit(revision)?.tree()?;
+
     let mut complexity_map = HashMap::new();
 
     for path in paths {
-        if path.exists() && path.is_file() {
-            if let Ok(complexity) = analyze_file_complexity(path) {
-                // Calcular caminho relativo ao repositório
-                let relative_path = path
-                    .strip_prefix(repo_path)
-                    .unwrap_or(path);
-                complexity_map.insert(relative_path.to_path_buf(), complexity);
-            }
+        let Ok(entry) = tree.get_path(path) else {
+            continue;
+        };
+        if entry.kind() != Some(ObjectType::Blob) {
+            continue;
         }
+
+        let blob = repo.find_blob(entry.id())?;
+        complexity_map.insert(path.clone(), analyze(path, blob.content()));
     }
 
-    complexity_map
+    Ok(complexity_map)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::commit_snapshot;
     use std::fs;
-    use std::io::Write;
     use tempfile::TempDir;
 
     #[test]
-    fn test_analyze_file_complexity_nonexistent() {
-        let path = Path::new("/nonexistent/file.rs");
-        let result = analyze_file_complexity(path).unwrap();
-        assert_eq!(result, 0.0);
+    fn test_analyze_content_complexity() {
+        let content = b"// comentario\nfn main() {\n\n    println!(\"oi\");\n}\n";
+        let lines = ComplexityMetric::Lines;
+        assert_eq!(
+            analyze_content_complexity(Path::new("main.rs"), content, lines).value,
+            3.0
+        );
+        assert_eq!(
+            analyze_content_complexity(Path::new("dados.desconhecido"), content, lines).value,
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_analyze_content_cyclomatic_complexity() {
+        let cyclomatic = ComplexityMetric::Cyclomatic;
+        let code = b"fn a(x: bool) {\n    if x {}\n}\nfn b() {}\n";
+        assert_eq!(
+            analyze_content_complexity(Path::new("lib.rs"), code, cyclomatic).value,
+            3.0
+        );
+
+        // Uma tabela de dados longa não tem decisões
+        let table = "pub const T: &[u32] = &[\n".to_string() + &"    1,\n".repeat(500) + "];\n";
+        assert_eq!(
+            analyze_content_complexity(Path::new("table.rs"), table.as_bytes(), cyclomatic).value,
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_analyze_content_cognitive_complexity() {
+        let nested = b"fn f(a: bool, b: bool) {\n    if a {\n        if b {}\n    }\n}\n";
+        let path = Path::new("lib.rs");
+
+        assert_eq!(
+            analyze_content_complexity(path, nested, ComplexityMetric::Cyclomatic).value,
+            3.0
+        );
+        assert_eq!(
+            analyze_content_complexity(path, nested, ComplexityMetric::Cognitive).value,
+            3.0
+        );
+        assert_eq!(
+            analyze_content_complexity(Path::new("run.sh"), nested, ComplexityMetric::Cognitive)
+                .value,
+            4.0
+        );
     }
 
     #[test]
-    fn test_analyze_multiple_files_empty_list() {
+    fn test_analyze_content_halstead_and_maintainability() {
+        let code = b"fn f(a: bool) -> u8 {\n    if a { 1 } else { 2 }\n}\n";
+        let path = Path::new("lib.rs");
+
+        let cyclomatic = analyze_content_complexity(path, code, ComplexityMetric::Cyclomatic);
+        let halstead = cyclomatic.halstead.unwrap();
+        let index = cyclomatic.maintainability_index.unwrap();
+        assert!(halstead.volume > 0.0);
+        assert!(index > 0.0 && index < 100.0);
+
+        let volume = analyze_content_complexity(path, code, ComplexityMetric::HalsteadVolume);
+        assert_eq!(volume.value, halstead.volume);
+        let effort = analyze_content_complexity(path, code, ComplexityMetric::HalsteadEffort);
+        assert_eq!(effort.value, halstead.effort);
+        let maintainability =
+            analyze_content_complexity(path, code, ComplexityMetric::Maintainability);
+        assert_eq!(maintainability.value, 100.0 - index);
+
+        // Sem análise sintática, não há Halstead
+        let script =
+            analyze_content_complexity(Path::new("run.sh"), code, ComplexityMetric::HalsteadVolume);
+        assert_eq!(script.halstead, None);
+        assert_eq!(script.value, 1.0);
+    }
+
+    #[test]
+    fn test_analyze_content_function_complexity() {
+        let code = b"\
+impl Parser {
+    fn parse(&self, a: bool) {
+        if a {
+            self.parse(false);
+        }
+    }
+}
+
+fn main() {}
+";
+        let path = Path::new("parser.rs");
+
+        let cyclomatic =
+            analyze_content_function_complexity(path, code, ComplexityMetric::Cyclomatic);
+        assert_eq!(cyclomatic.len(), 2);
+        assert_eq!(cyclomatic["Parser::parse"], 2.0);
+        assert_eq!(cyclomatic["main"], 1.0);
+
+        let lines = analyze_content_function_complexity(path, code, ComplexityMetric::Lines);
+        assert_eq!(lines["Parser::parse"], 5.0);
+        assert_eq!(lines["main"], 1.0);
+
+        // if (1) + recursão (1)
+        let cognitive =
+            analyze_content_function_complexity(path, code, ComplexityMetric::Cognitive);
+        assert_eq!(cognitive["Parser::parse"], 2.0);
+
+        assert!(analyze_content_function_complexity(
+            Path::new("run.sh"),
+            code,
+            ComplexityMetric::Cyclomatic
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_indentation_complexity() {
+        let yaml = "jobs:\n  build:\n    steps:\n      - run: make\n\n      - run: test\n";
+        assert_eq!(
+            indentation_complexity(yaml),
+            IndentationComplexity { total: 9, max: 3 }
+        );
+
+        let tabs = "a\n\tb\n\t\tc\n";
+        assert_eq!(
+            indentation_complexity(tabs),
+            IndentationComplexity { total: 3, max: 2 }
+        );
+
+        assert_eq!(indentation_complexity(""), IndentationComplexity::default());
+    }
+
+    #[test]
+    fn test_fallback_is_flagged() {
+        let script = b"if true; then\n    echo oi\nfi\n";
+        let path = Path::new("run.sh");
+
+        assert!(analyze_content_complexity(path, script, ComplexityMetric::Cyclomatic).fallback);
+        assert!(!analyze_content_complexity(path, script, ComplexityMetric::Indentation).fallback);
+        assert!(!analyze_content_complexity(path, script, ComplexityMetric::Lines).fallback);
+    }
+
+    #[test]
+    fn test_cyclomatic_falls_back_to_indentation() {
+        let cyclomatic = ComplexityMetric::Cyclomatic;
+        let script = b"if [ -f x ]; then\n    for f in *; do\n        echo $f\n    done\nfi\n";
+
+        assert_eq!(
+            analyze_content_complexity(Path::new("build.sh"), script, cyclomatic),
+            FileComplexity {
+                value: 4.0,
+                max_indentation: Some(2),
+                fallback: true,
+                ..FileComplexity::default()
+            }
+        );
+        assert_eq!(
+            analyze_content_complexity(Path::new("logo.png"), &[0x89, 0xff, 0x00], cyclomatic)
+                .value,
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_analyze_revision_nonexistent_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let revision = commit_snapshot(
+            &repo,
+            &[("main.rs", "fn main() {}\n")],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
+
+        let paths = vec![PathBuf::from("nao/existe.rs")];
+        let result =
+            analyze_revision(temp_dir.path(), revision, &paths, ComplexityMetric::Lines).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_revision_empty_list() {
         let temp_dir = TempDir::new().unwrap();
-        let paths: Vec<PathBuf> = vec![];
-        let result = analyze_multiple_files(&paths, temp_dir.path());
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let revision = commit_snapshot(
+            &repo,
+            &[("main.rs", "fn main() {}\n")],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
+
+        let result =
+            analyze_revision(temp_dir.path(), revision, &[], ComplexityMetric::Lines).unwrap();
         assert!(result.is_empty());
     }
 
     #[test]
-    fn test_analyze_multiple_files_with_existing_file() {
+    fn test_analyze_revision_ignores_working_directory() {
         let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("test.rs");
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let revision = commit_snapshot(
+            &repo,
+            &[("test.rs", "fn main() {}\n")],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
 
-        let mut file = fs::File::create(&file_path).unwrap();
-        writeln!(file, "fn main() {{}}").unwrap();
+        // Edição não commitada: não deve afetar o resultado
+        fs::write(temp_dir.path().join("test.rs"), "fn a() {}\nfn b() {}\n").unwrap();
 
-        let paths = vec![file_path.clone()];
-        let result = analyze_multiple_files(&paths, temp_dir.path());
+        let paths = vec![PathBuf::from("test.rs")];
+        let result =
+            analyze_revision(temp_dir.path(), revision, &paths, ComplexityMetric::Lines).unwrap();
+        assert_eq!(result[&PathBuf::from("test.rs")].value, 1.0);
+    }
+
+    #[test]
+    fn test_analyze_revision_bare_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(temp_dir.path()).unwrap();
+        let revision = commit_snapshot(
+            &repo,
+            &[("lib.py", "def f():\n    return 1\n")],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
 
-        assert!(!result.is_empty());
+        let paths = vec![PathBuf::from("lib.py")];
+        let result =
+            analyze_revision(temp_dir.path(), revision, &paths, ComplexityMetric::Lines).unwrap();
+        assert_eq!(result[&PathBuf::from("lib.py")].value, 2.0);
     }
 }
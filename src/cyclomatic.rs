@@ -0,0 +1,187 @@
+use crate::syntax::{Language, SourceCode, TokenKind};
+
+/// Complexidade ciclomática de cada função, na ordem de `code.functions`: 1 mais o número de
+/// pontos de decisão da função (sem contar os das funções aninhadas nela).
+pub fn function_complexities(code: &SourceCode) -> Vec<usize> {
+    (0..code.functions.len())
+        .map(|function| {
+            1 + code
+                .own_tokens(function)
+                .filter(|&i| is_decision(code, i))
+                .count()
+        })
+        .collect()
+}
+
+/// Complexidade ciclomática do arquivo: a soma da complexidade das funções, mais os pontos de
+/// decisão do código fora de funções nas linguagens que o admitem.
+pub fn file_complexity(code: &SourceCode) -> usize {
+    let functions: usize = function_complexities(code).iter().sum();
+
+    let top_level = if code.language.has_top_level_code() {
+        code.top_level_tokens()
+            .filter(|&i| is_decision(code, i))
+            .count()
+    } else {
+        0
+    };
+
+    functions + top_level
+}
+
+/// Se o token `i` abre um caminho a mais no fluxo de controle: condicionais, laços, casos,
+/// tratadores de exceção e operadores lógicos de curto-circuito.
+fn is_decision(code: &SourceCode, i: usize) -> bool {
+    let token = &code.tokens[i];
+    let keywords: &[&str] = match code.language {
+        Language::Rust => &["if", "while", "for", "=>", "&&", "||"],
+        Language::Python => &["if", "elif", "for", "while", "except", "case", "and", "or"],
+        Language::JavaScript => &["if", "for", "while", "case", "catch", "&&", "||", "??"],
+        Language::Java => &["if", "for", "while", "case", "catch", "&&", "||"],
+        Language::Go => &["if", "for", "case", "&&", "||"],
+        Language::C => &[
+            "if", "for", "while", "case", "catch", "&&", "||", "and", "or",
+        ],
+    };
+
+    if token.kind == TokenKind::Str {
+        return false;
+    }
+    if keywords.contains(&token.text) {
+        return true;
+    }
+
+    token.is("?") && is_ternary(code, i)
+}
+
+/// Distingue o `?` do operador ternário do `?` de parâmetros opcionais (`x?: T`) e de
+/// curingas de tipos genéricos (`List<? extends T>`).
+pub fn is_ternary(code: &SourceCode, i: usize) -> bool {
+    if !matches!(
+        code.language,
+        Language::JavaScript | Language::Java | Language::C
+    ) {
+        return false;
+    }
+
+    let next = code.tokens.get(i + 1);
+    !next.is_some_and(|next| {
+        [":", ")", ",", "=", ">", "extends", "super"]
+            .iter()
+            .any(|text| next.is(text))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complexities(language: Language, source: &str) -> (Vec<usize>, usize) {
+        let code = SourceCode::parse(language, source);
+        (function_complexities(&code), file_complexity(&code))
+    }
+
+    #[test]
+    fn test_rust_complexity() {
+        let source = r#"
+fn simple() -> i32 { 1 }
+
+fn branches(x: Option<i32>, a: bool, b: bool) -> i32 {
+    // if e for em comentários e strings não contam
+    let _ = "if for while";
+    if a && b {
+        return 1;
+    } else if a || b {
+        return 2;
+    }
+    for i in 0..3 {
+        while false {}
+    }
+    match x {
+        Some(v) => v,
+        None => 0,
+    }
+}
+"#;
+        // branches: 1 + if + && + if + || + for + while + 2 braços
+        assert_eq!(complexities(Language::Rust, source), (vec![1, 9], 10));
+    }
+
+    #[test]
+    fn test_python_complexity() {
+        let source = "\
+def f(items):
+    for item in items:
+        if item > 0 and item < 10:
+            yield item
+        elif item == 0:
+            pass
+    try:
+        g()
+    except ValueError:
+        pass
+
+if __name__ == '__main__':
+    f([])
+";
+        // f: 1 + for + if + and + elif + except; mais o if do módulo
+        assert_eq!(complexities(Language::Python, source), (vec![6], 7));
+    }
+
+    #[test]
+    fn test_javascript_complexity() {
+        let source = "\
+function f(a, b) {
+  const c = a ? b : null;
+  const d = a?.b ?? 0;
+  switch (b) {
+    case 1: return 1;
+    case 2: return 2;
+    default: return c || d;
+  }
+}
+const g = (x?: number) => x;
+";
+        // f: 1 + ?: + ?? + 2 case + ||; o ?. e o parâmetro opcional não contam
+        assert_eq!(complexities(Language::JavaScript, source), (vec![6], 6));
+    }
+
+    #[test]
+    fn test_java_complexity() {
+        let source = "\
+class A {
+  int f(List<? extends Number> xs) {
+    try {
+      for (Number x : xs) { if (x == null) continue; }
+    } catch (Exception e) {
+      return -1;
+    }
+    return xs.isEmpty() ? 0 : 1;
+  }
+}
+";
+        assert_eq!(complexities(Language::Java, source), (vec![5], 5));
+    }
+
+    #[test]
+    fn test_go_and_c_complexity() {
+        let go = "func f(x int) int {\n\tif x > 0 && x < 10 {\n\t\treturn x\n\t}\n\treturn 0\n}\n";
+        assert_eq!(complexities(Language::Go, go), (vec![3], 3));
+
+        let c = "int f(int x) {\n  while (x) { x = x > 1 ? x - 1 : 0; }\n  return x;\n}\n\
+                 int g(void) { return 0; }\n";
+        assert_eq!(complexities(Language::C, c), (vec![3, 1], 4));
+    }
+
+    #[test]
+    fn test_nested_functions_are_counted_separately() {
+        let source = "fn outer() {\n    fn inner(a: bool) { if a {} }\n    if true {}\n}\n";
+        assert_eq!(complexities(Language::Rust, source), (vec![2, 2], 4));
+    }
+
+    #[test]
+    fn test_file_without_functions() {
+        let source = "pub const TABLE: [u8; 3] = [1, 2, 3];\n";
+        assert_eq!(complexities(Language::Rust, source), (vec![], 0));
+    }
+}
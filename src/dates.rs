@@ -0,0 +1,236 @@
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Qual extremo do período uma data delimita. Uma data sem horário cobre o dia inteiro:
+/// como início vale a partir de 00:00:00, como fim vale até 23:59:59.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateBound {
+    Start,
+    End,
+}
+
+/// Unidades aceitas em datas relativas e quanto cada uma representa.
+enum Span {
+    Duration(Duration),
+    Months(u32),
+}
+
+/// Interpreta uma data absoluta ou relativa a `now`. Datas e horários sem fuso são lidos
+/// no fuso `tz` (o fuso local, na linha de comando), como faz o `git log`.
+///
+/// Formatos aceitos:
+/// - `2024-01-15` (o dia inteiro, conforme `bound`)
+/// - `2024-01-15T10:30:00` ou RFC 3339 com fuso, como `2024-01-15T10:30:00-03:00`
+/// - relativos: `6 months ago`, `2 weeks`, `2w`, `3d`, `1y`, `6.months.ago`, `3 dias atrás`
+/// - `now`
+pub fn parse_date_expression<Tz: TimeZone>(
+    s: &str,
+    bound: DateBound,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Option<DateTime<Utc>> {
+    parse_absolute(s.trim(), bound, tz).or_else(|| parse_relative(s, now))
+}
+
+fn parse_absolute<Tz: TimeZone>(s: &str, bound: DateBound, tz: &Tz) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let time = match bound {
+            DateBound::Start => NaiveTime::MIN,
+            DateBound::End => NaiveTime::from_hms_opt(23, 59, 59)?,
+        };
+        return in_timezone(date.and_time(time), tz);
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    .and_then(|datetime| in_timezone(datetime, tz))
+}
+
+/// Converte um horário local de `tz` para UTC. Num horário ambíguo (fim do horário de
+/// verão) vale o primeiro; num horário que não existe, o primeiro instante depois dele.
+fn in_timezone<Tz: TimeZone>(datetime: NaiveDateTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&datetime)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(datetime + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+fn parse_relative(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let normalized = s.trim().to_lowercase().replace(['.', '_'], " ");
+    let normalized = normalized.trim();
+
+    if normalized == "now" || normalized == "agora" {
+        return Some(now);
+    }
+
+    let expression = ["ago", "atrás", "atras"]
+        .iter()
+        .find_map(|suffix| normalized.strip_suffix(suffix))
+        .unwrap_or(normalized)
+        .trim();
+
+    let digits_end = expression
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(expression.len());
+    let amount: u32 = expression[..digits_end].parse().ok()?;
+    let unit = expression[digits_end..].trim();
+
+    match parse_unit(unit, amount)? {
+        Span::Duration(duration) => now.checked_sub_signed(duration),
+        Span::Months(months) => now.checked_sub_months(Months::new(months)),
+    }
+}
+
+fn parse_unit(unit: &str, amount: u32) -> Option<Span> {
+    let amount_i64 = i64::from(amount);
+
+    let span = match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" | "segundo" | "segundos" => {
+            Span::Duration(Duration::seconds(amount_i64))
+        }
+        "min" | "mins" | "minute" | "minutes" | "minuto" | "minutos" => {
+            Span::Duration(Duration::minutes(amount_i64))
+        }
+        "h" | "hr" | "hrs" | "hour" | "hours" | "hora" | "horas" => {
+            Span::Duration(Duration::hours(amount_i64))
+        }
+        "d" | "day" | "days" | "dia" | "dias" => Span::Duration(Duration::days(amount_i64)),
+        "w" | "wk" | "week" | "weeks" | "semana" | "semanas" => {
+            Span::Duration(Duration::weeks(amount_i64))
+        }
+        "mo" | "month" | "months" | "mes" | "mês" | "meses" => Span::Months(amount),
+        "y" | "yr" | "year" | "years" | "ano" | "anos" => Span::Months(amount.checked_mul(12)?),
+        _ => return None,
+    };
+
+    Some(span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap()
+    }
+
+    fn parse(s: &str) -> Option<DateTime<Utc>> {
+        parse_date_expression(s, DateBound::Start, now(), &Utc)
+    }
+
+    fn brasilia() -> FixedOffset {
+        FixedOffset::west_opt(3 * 3600).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_only() {
+        assert_eq!(
+            parse("2024-01-15"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_date_only_end_of_day() {
+        assert_eq!(
+            parse_date_expression("2024-12-31", DateBound::End, now(), &Utc),
+            Some(Utc.with_ymd_and_hms(2024, 12, 31, 23, 59, 59).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_date_only_in_local_timezone() {
+        assert_eq!(
+            parse_date_expression("2024-12-31", DateBound::Start, now(), &brasilia()),
+            Some(Utc.with_ymd_and_hms(2024, 12, 31, 3, 0, 0).unwrap())
+        );
+        assert_eq!(
+            parse_date_expression("2024-12-31", DateBound::End, now(), &brasilia()),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 2, 59, 59).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_explicit_timezone_ignores_local_timezone() {
+        assert_eq!(
+            parse_date_expression("2024-01-15T10:30:00Z", DateBound::End, now(), &brasilia()),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_rfc3339_with_timezone() {
+        assert_eq!(
+            parse("2024-01-15T10:30:00-03:00"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 13, 30, 0).unwrap())
+        );
+        assert_eq!(
+            parse("2024-01-15T10:30:00Z"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime_without_timezone() {
+        assert_eq!(
+            parse("2024-01-15 10:30:00"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_long_form() {
+        assert_eq!(
+            parse("6 months ago"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap())
+        );
+        assert_eq!(parse("3 days ago"), Some(now() - Duration::days(3)));
+        assert_eq!(parse("2.weeks.ago"), Some(now() - Duration::weeks(2)));
+        assert_eq!(
+            parse("1 year"),
+            Some(Utc.with_ymd_and_hms(2023, 7, 15, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_short_form() {
+        assert_eq!(parse("2w"), Some(now() - Duration::weeks(2)));
+        assert_eq!(parse("36h"), Some(now() - Duration::hours(36)));
+        assert_eq!(
+            parse("3mo"),
+            Some(Utc.with_ymd_and_hms(2024, 4, 15, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_portuguese() {
+        assert_eq!(parse("3 dias atrás"), Some(now() - Duration::days(3)));
+        assert_eq!(parse("2 semanas"), Some(now() - Duration::weeks(2)));
+    }
+
+    #[test]
+    fn test_parse_now() {
+        assert_eq!(parse("now"), Some(now()));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("v1.2.0").is_none());
+        assert!(parse("main").is_none());
+        assert!(parse("2 fortnights ago").is_none());
+        assert!(parse("").is_none());
+    }
+}
@@ -0,0 +1,325 @@
+use anyhow::{bail, Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::RegexSet;
+use std::path::Path;
+
+/// Padrões que identificam contas de automação (Dependabot, Renovate, bots de release...),
+/// testados contra o nome e o e-mail do autor.
+const BOT_PATTERNS: &[&str] = &[
+    r"(?i)\[bot\]",
+    r"(?i)^(dependabot|renovate|greenkeeper|snyk-bot|github-actions|pre-commit-ci|mergify|imgbot|allcontributors)\b",
+    r"(?i)(^|[-_. ])bot$",
+    r"(?i)(^|[-_.])bot@",
+];
+
+/// Filtro de caminhos montado a partir de `--include`/`--exclude`.
+///
+/// Cada padrão é um glob (`*.rs`, `**/*.generated.ts`, `src/{a,b}/*`) em que `*` não
+/// atravessa `/`. Um padrão sem `/` vale em qualquer profundidade e casa um nome de arquivo
+/// ou de diretório inteiro (`test` casa `test/x.rs` e `src/test/y.rs`, mas não
+/// `src/contest.rs`); um padrão com `/` é relativo à raiz do repositório. Padrões que casam
+/// um diretório valem para tudo dentro dele.
+///
+/// A sintaxe de pathspec do git também é aceita: `:(exclude)padrão`, `:!padrão` e
+/// `:^padrão` excluem, `:(icase)` ignora maiúsculas, `:(literal)` desativa o glob, e
+/// `:(top)`/`:/` são aceitos (os caminhos já são relativos à raiz).
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+#[derive(Debug, Default)]
+struct Magic {
+    exclude: bool,
+    icase: bool,
+    literal: bool,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let mut include_builder = GlobSetBuilder::new();
+        let mut exclude_builder = GlobSetBuilder::new();
+        let mut has_include = false;
+        let mut has_exclude = false;
+
+        let patterns = include
+            .iter()
+            .map(|p| (p, false))
+            .chain(exclude.iter().map(|p| (p, true)));
+
+        for (raw, from_exclude) in patterns {
+            let (magic, pattern) = parse_magic(raw)?;
+            let builder = if magic.exclude || from_exclude {
+                has_exclude = true;
+                &mut exclude_builder
+            } else {
+                has_include = true;
+                &mut include_builder
+            };
+            add_pattern(builder, pattern, &magic)
+                .with_context(|| format!("Padrão de caminho inválido: {}", raw))?;
+        }
+
+        Ok(Self {
+            include: has_include.then(|| include_builder.build()).transpose()?,
+            exclude: has_exclude.then(|| exclude_builder.build()).transpose()?,
+        })
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        if let Some(include) = &self.include {
+            if !include.is_match(path) {
+                return false;
+            }
+        }
+
+        !self
+            .exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(path))
+    }
+}
+
+/// Filtro de autores montado a partir de `--include-author`/`--exclude-author`.
+///
+/// Cada padrão é uma expressão regular testada contra o nome e contra o e-mail do autor (já
+/// resolvidos pelo mailmap); basta casar um dos dois. Com `exclude_bots`, os padrões de
+/// [`BOT_PATTERNS`] entram na lista de exclusão.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorFilter {
+    include: Option<RegexSet>,
+    exclude: Option<RegexSet>,
+}
+
+impl AuthorFilter {
+    pub fn new(include: &[String], exclude: &[String], exclude_bots: bool) -> Result<Self> {
+        let bots = if exclude_bots { BOT_PATTERNS } else { &[] };
+        let exclude: Vec<&str> = exclude
+            .iter()
+            .map(String::as_str)
+            .chain(bots.iter().copied())
+            .collect();
+
+        let build = |patterns: &[&str]| -> Result<Option<RegexSet>> {
+            if patterns.is_empty() {
+                return Ok(None);
+            }
+            for pattern in patterns {
+                regex::Regex::new(pattern)
+                    .with_context(|| format!("Padrão de autor inválido: {}", pattern))?;
+            }
+            Ok(Some(RegexSet::new(patterns)?))
+        };
+
+        let include: Vec<&str> = include.iter().map(String::as_str).collect();
+        Ok(Self {
+            include: build(&include)?,
+            exclude: build(&exclude)?,
+        })
+    }
+
+    pub fn matches(&self, name: &str, email: &str) -> bool {
+        let is_match = |set: &RegexSet| set.is_match(name) || set.is_match(email);
+
+        if let Some(include) = &self.include {
+            if !is_match(include) {
+                return false;
+            }
+        }
+
+        !self.exclude.as_ref().is_some_and(is_match)
+    }
+}
+
+/// Separa a "mágica" de pathspec (`:(exclude,icase)`, `:!`, `:^`, `:/`) do padrão.
+fn parse_magic(raw: &str) -> Result<(Magic, &str)> {
+    let mut magic = Magic::default();
+
+    let Some(rest) = raw.strip_prefix(':') else {
+        return Ok((magic, raw));
+    };
+
+    if let Some(rest) = rest.strip_prefix('(') {
+        let Some((names, pattern)) = rest.split_once(')') else {
+            bail!("Pathspec sem ')' de fechamento: {}", raw);
+        };
+
+        for name in names.split(',').map(str::trim) {
+            match name {
+                "exclude" => magic.exclude = true,
+                "icase" => magic.icase = true,
+                "literal" => magic.literal = true,
+                "glob" | "top" | "" => {}
+                _ => bail!("Mágica de pathspec não suportada: {}", name),
+            }
+        }
+        return Ok((magic, pattern));
+    }
+
+    // Forma curta: qualquer combinação de '!'/'^' (exclude) e '/' (top), e um ':' opcional
+    let pattern = rest.trim_start_matches(['!', '^', '/']);
+    magic.exclude = rest[..rest.len() - pattern.len()].contains(['!', '^']);
+    Ok((magic, pattern.strip_prefix(':').unwrap_or(pattern)))
+}
+
+fn add_pattern(builder: &mut GlobSetBuilder, pattern: &str, magic: &Magic) -> Result<()> {
+    let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+    if trimmed.is_empty() {
+        bail!("padrão vazio");
+    }
+
+    let escaped;
+    let glob = if magic.literal {
+        escaped = globset::escape(trimmed);
+        escaped.as_str()
+    } else {
+        trimmed
+    };
+
+    let anchored = pattern.starts_with('/') || trimmed.contains('/');
+    let base = if anchored {
+        glob.to_string()
+    } else {
+        format!("**/{}", glob)
+    };
+
+    for candidate in [base.clone(), format!("{}/**", base)] {
+        builder.add(
+            GlobBuilder::new(&candidate)
+                .literal_separator(true)
+                .case_insensitive(magic.icase)
+                .build()?,
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let to_strings =
+            |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        PathFilter::new(&to_strings(include), &to_strings(exclude)).unwrap()
+    }
+
+    fn matches(filter: &PathFilter, path: &str) -> bool {
+        filter.matches(Path::new(path))
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let f = filter(&[], &[]);
+        assert!(matches(&f, "src/main.rs"));
+    }
+
+    #[test]
+    fn test_exclude_name_does_not_match_substring() {
+        let f = filter(&[], &["test"]);
+        assert!(!matches(&f, "test/foo.rs"));
+        assert!(!matches(&f, "src/test/foo.rs"));
+        assert!(!matches(&f, "src/test"));
+        assert!(matches(&f, "src/contest.rs"));
+        assert!(matches(&f, "tests/foo.rs"));
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        let f = filter(&[], &["**/*.generated.ts"]);
+        assert!(!matches(&f, "web/api.generated.ts"));
+        assert!(!matches(&f, "api.generated.ts"));
+        assert!(matches(&f, "web/api.ts"));
+
+        let f = filter(&["*.rs"], &[]);
+        assert!(matches(&f, "src/deep/mod.rs"));
+        assert!(!matches(&f, "README.md"));
+    }
+
+    #[test]
+    fn test_star_does_not_cross_directories() {
+        let f = filter(&["src/*.rs"], &[]);
+        assert!(matches(&f, "src/main.rs"));
+        assert!(!matches(&f, "src/core/foo.rs"));
+    }
+
+    #[test]
+    fn test_anchored_directory() {
+        let f = filter(&["src/core"], &[]);
+        assert!(matches(&f, "src/core/foo.rs"));
+        assert!(!matches(&f, "lib/src/core/foo.rs"));
+    }
+
+    #[test]
+    fn test_pathspec_exclude_magic() {
+        let f = filter(&["src", ":(exclude)src/vendor", ":!*.lock"], &[]);
+        assert!(matches(&f, "src/main.rs"));
+        assert!(!matches(&f, "src/vendor/lib.rs"));
+        assert!(!matches(&f, "src/Cargo.lock"));
+        assert!(!matches(&f, "docs/guide.md"));
+
+        let f = filter(&[":^docs"], &[]);
+        assert!(matches(&f, "src/main.rs"));
+        assert!(!matches(&f, "docs/guide.md"));
+    }
+
+    #[test]
+    fn test_pathspec_icase_and_literal() {
+        let f = filter(&[":(icase)readme.md"], &[]);
+        assert!(matches(&f, "README.md"));
+
+        let f = filter(&[":(literal)a[1].txt"], &[]);
+        assert!(matches(&f, "a[1].txt"));
+        assert!(!matches(&f, "a1.txt"));
+    }
+
+    fn authors(include: &[&str], exclude: &[&str], exclude_bots: bool) -> AuthorFilter {
+        let to_strings =
+            |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        AuthorFilter::new(&to_strings(include), &to_strings(exclude), exclude_bots).unwrap()
+    }
+
+    #[test]
+    fn test_author_filter_regex_on_name_and_email() {
+        let f = authors(&[], &["^Release", r"@ci\.example\.com$"], false);
+        assert!(!f.matches("Release Manager", "release@example.com"));
+        assert!(!f.matches("Jenkins", "jenkins@ci.example.com"));
+        assert!(f.matches("Ana Silva", "ana@example.com"));
+
+        let f = authors(&[r"@example\.com$"], &["^Bob"], false);
+        assert!(f.matches("Ana Silva", "ana@example.com"));
+        assert!(!f.matches("Bob", "bob@example.com"));
+        assert!(!f.matches("Carla", "carla@other.org"));
+    }
+
+    #[test]
+    fn test_author_filter_bots() {
+        let f = authors(&[], &[], true);
+        assert!(!f.matches(
+            "dependabot[bot]",
+            "49699333+dependabot[bot]@users.noreply.github.com"
+        ));
+        assert!(!f.matches("Renovate Bot", "bot@renovateapp.com"));
+        assert!(!f.matches("github-actions", "actions@github.com"));
+        assert!(!f.matches("Release Bot", "release@example.com"));
+        assert!(!f.matches("ci", "release-bot@example.com"));
+        assert!(f.matches("Talbot", "talbot@example.com"));
+        assert!(f.matches("Ana Silva", "ana@example.com"));
+
+        assert!(authors(&[], &[], false).matches("dependabot[bot]", "x@y"));
+    }
+
+    #[test]
+    fn test_invalid_author_pattern() {
+        assert!(AuthorFilter::new(&["(".to_string()], &[], false).is_err());
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(PathFilter::new(&[":(exclude".to_string()], &[]).is_err());
+        assert!(PathFilter::new(&[":(attr:x)src".to_string()], &[]).is_err());
+        assert!(PathFilter::new(&["src/[".to_string()], &[]).is_err());
+    }
+}
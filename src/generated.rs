@@ -0,0 +1,155 @@
+use crate::types::ExclusionReason;
+use std::path::Path;
+
+/// Nomes de lockfiles de gerenciadores de pacotes conhecidos.
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "composer.lock",
+    "Gemfile.lock",
+    "Pipfile.lock",
+    "poetry.lock",
+    "uv.lock",
+    "go.sum",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+    "Package.resolved",
+    "packages.lock.json",
+    "gradle.lockfile",
+    "flake.lock",
+];
+
+/// Marcadores que ferramentas de geração de código costumam pôr no cabeçalho do arquivo.
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "Code generated by"];
+
+/// Os mesmos, comparados sem diferenciar maiúsculas de minúsculas.
+const GENERATED_MARKERS_ICASE: &[&str] =
+    &["auto-generated", "autogenerated", "automatically generated"];
+
+/// Quantas linhas do início do arquivo são procuradas pelos marcadores.
+const HEADER_LINES: usize = 10;
+
+/// Tamanho médio de linha a partir do qual o arquivo é considerado minificado.
+const MINIFIED_AVG_LINE_LEN: usize = 200;
+
+/// Arquivos menores que isso não são avaliados pelo tamanho de linha.
+const MINIFIED_MIN_SIZE: usize = 1024;
+
+/// Classifica arquivos que não são escritos à mão: lockfiles, pelo nome; arquivos com
+/// cabeçalho de código gerado; e arquivos minificados, pelo nome (`.min.js`) ou pelo
+/// tamanho médio das linhas.
+///
+/// `content` é o conteúdo na revisão analisada, ou `None` se o arquivo não existe nela.
+pub fn classify(path: &Path, content: Option<&[u8]>) -> Option<ExclusionReason> {
+    let name = path.file_name()?.to_str()?;
+
+    if LOCKFILES.contains(&name) {
+        return Some(ExclusionReason::Lockfile);
+    }
+    if name.contains(".min.") {
+        return Some(ExclusionReason::Minified);
+    }
+
+    let text = std::str::from_utf8(content?).ok()?;
+
+    if has_generated_header(text) {
+        Some(ExclusionReason::GeneratedHeader)
+    } else if is_minified(text) {
+        Some(ExclusionReason::Minified)
+    } else {
+        None
+    }
+}
+
+fn has_generated_header(text: &str) -> bool {
+    text.lines().take(HEADER_LINES).any(|line| {
+        let lower = line.to_lowercase();
+        GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+            || GENERATED_MARKERS_ICASE
+                .iter()
+                .any(|marker| lower.contains(marker))
+    })
+}
+
+fn is_minified(text: &str) -> bool {
+    let lines = text.lines().filter(|line| !line.trim().is_empty()).count();
+    text.len() >= MINIFIED_MIN_SIZE && lines > 0 && text.len() / lines > MINIFIED_AVG_LINE_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_text(path: &str, content: &str) -> Option<ExclusionReason> {
+        classify(Path::new(path), Some(content.as_bytes()))
+    }
+
+    #[test]
+    fn test_lockfiles_by_name() {
+        assert_eq!(
+            classify(Path::new("Cargo.lock"), None),
+            Some(ExclusionReason::Lockfile)
+        );
+        assert_eq!(
+            classify_text("web/package-lock.json", "{}"),
+            Some(ExclusionReason::Lockfile)
+        );
+        assert_eq!(classify_text("src/lock.rs", "fn lock() {}\n"), None);
+    }
+
+    #[test]
+    fn test_generated_headers() {
+        assert_eq!(
+            classify_text(
+                "api.pb.go",
+                "// Code generated by protoc-gen-go. DO NOT EDIT.\n"
+            ),
+            Some(ExclusionReason::GeneratedHeader)
+        );
+        assert_eq!(
+            classify_text("schema.rs", "// @generated\nuse diesel::*;\n"),
+            Some(ExclusionReason::GeneratedHeader)
+        );
+        assert_eq!(
+            classify_text("Resources.cs", "// <auto-generated>\nclass R {}\n"),
+            Some(ExclusionReason::GeneratedHeader)
+        );
+    }
+
+    #[test]
+    fn test_marker_after_header_is_ignored() {
+        let mut content = "fn main() {}\n".repeat(HEADER_LINES);
+        content.push_str("// DO NOT EDIT\n");
+        assert_eq!(classify_text("src/main.rs", &content), None);
+    }
+
+    #[test]
+    fn test_minified_files() {
+        assert_eq!(
+            classify_text("dist/app.min.js", "var a=1;"),
+            Some(ExclusionReason::Minified)
+        );
+
+        let long_line = "var a=1;".repeat(500);
+        assert_eq!(
+            classify_text("dist/bundle.js", &long_line),
+            Some(ExclusionReason::Minified)
+        );
+
+        let normal = "let a = 1;\n".repeat(500);
+        assert_eq!(classify_text("src/app.js", &normal), None);
+    }
+
+    #[test]
+    fn test_binary_content_is_not_classified() {
+        assert_eq!(
+            classify(Path::new("logo.png"), Some(&[0xff, 0xfe, 0x00])),
+            None
+        );
+    }
+}
@@ -1,51 +1,429 @@
+use crate::attributes::GitAttributes;
+use crate::cache::ChangeCache;
+use crate::dates::{self, DateBound};
+use crate::filters::{AuthorFilter, PathFilter};
+use crate::generated;
+use crate::identity::{AuthorKey, IdentityResolver};
+use crate::syntax::{self, Function, Language, SourceCode};
+use crate::types::{BinaryChurn, ExclusionReason, MassChange};
 use anyhow::{Context, Result};
-use chrono::{DateTime, TimeZone, Utc};
-use git2::Repository;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use clap::ValueEnum;
+use git2::{Delta, DiffFindOptions, DiffOptions, Oid, Repository, RevparseMode, Revwalk, Sort};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 
+/// Similaridade mínima (0–100) padrão para considerar um arquivo renomeado.
+pub const DEFAULT_RENAME_THRESHOLD: u16 = 50;
+
+/// Número padrão de commits amostrados para a tendência de complexidade.
+pub const DEFAULT_TREND_SAMPLES: usize = 10;
+
+/// Como commits de merge entram na contagem de churn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum MergePolicy {
+    /// Ignora commits de merge; as mudanças contam apenas nos commits dos ramos
+    Skip,
+    /// Segue só o primeiro pai, contando cada merge como um único commit do ramo principal
+    FirstParent,
+    /// Conta em merges apenas o que difere de todos os pais (resolução de conflitos)
+    #[default]
+    Conflicts,
+}
+
+/// O que fazer com commits que passam dos limites de mudança em massa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum MassChangePolicy {
+    /// Não conta churn nem autoria do commit
+    #[default]
+    Exclude,
+    /// Reduz o churn do commit na proporção em que ele passa do limite
+    Dampen,
+}
+
+/// Unidade do ranking de hotspots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Granularity {
+    /// Arquivos inteiros
+    #[default]
+    File,
+    /// Funções e métodos, identificados como `arquivo::função`
+    Function,
+}
+
+/// Qual data do commit é comparada com `--since`/`--until`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum DateField {
+    /// Data em que a mudança foi escrita
+    Author,
+    /// Data em que o commit foi gravado (a mesma usada pelo `git log --since`)
+    #[default]
+    Committer,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub date_field: DateField,
+    /// `None` desativa a detecção de renomeações.
+    pub rename_threshold: Option<u16>,
+    pub merge_policy: MergePolicy,
+    /// Arquivo de aliases de autores no formato `.mailmap`, somado ao do repositório.
+    pub alias_file: Option<PathBuf>,
+    pub author_key: AuthorKey,
+    /// Autores cujos commits entram na análise.
+    pub author_filter: AuthorFilter,
+    /// Revisões (`v1.2.0`, `feature`) ou intervalos (`main..feature`, `A...B`) a percorrer.
+    /// Vazio significa `HEAD`.
+    pub revisions: Vec<String>,
+    /// Percorre todos os ramos locais.
+    pub all_branches: bool,
+    /// Número de threads que calculam diffs em paralelo.
+    pub jobs: usize,
+    /// Reaproveita os diffs já calculados, guardados no diretório do git.
+    pub use_cache: bool,
+    /// Caminhos (atuais, depois de seguir renomeações) considerados na análise.
+    pub path_filter: PathFilter,
+    /// Exclui arquivos marcados como gerados, vendorizados ou sem diff no `.gitattributes`.
+    pub use_gitattributes: bool,
+    /// Exclui lockfiles, arquivos minificados e com cabeçalho de código gerado.
+    pub detect_generated: bool,
+    /// Ignora diferenças de espaço em branco ao contar linhas (`git diff -w`).
+    pub ignore_whitespace: bool,
+    /// Ignora mudanças na quantidade de espaço em branco (`git diff -b`).
+    pub ignore_whitespace_change: bool,
+    /// Arquivo no formato do `.git-blame-ignore-revs` com commits que não contam churn nem
+    /// autoria. Caminhos relativos partem da raiz do repositório.
+    pub ignore_revs_file: Option<PathBuf>,
+    /// Commits que alteram mais arquivos que isso são mudanças em massa.
+    pub max_files_per_commit: Option<usize>,
+    /// Commits que alteram mais linhas que isso são mudanças em massa.
+    pub max_lines_per_commit: Option<usize>,
+    pub mass_change_policy: MassChangePolicy,
+    /// Mantém os arquivos que não existem mais na revisão analisada.
+    pub include_deleted: bool,
+    /// Com [`Granularity::Function`], o churn também é distribuído entre as funções.
+    pub granularity: Granularity,
+    /// Número de commits, espaçados por igual na caminhada, em que a tendência de
+    /// complexidade é amostrada; 0 desativa a amostragem. O padrão, como na linha de comando,
+    /// é [`DEFAULT_TREND_SAMPLES`].
+    pub trend_samples: usize,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            since: None,
+            until: None,
+            date_field: DateField::default(),
+            rename_threshold: Some(DEFAULT_RENAME_THRESHOLD),
+            merge_policy: MergePolicy::default(),
+            alias_file: None,
+            author_key: AuthorKey::default(),
+            author_filter: AuthorFilter::default(),
+            revisions: Vec::new(),
+            all_branches: false,
+            jobs: default_jobs(),
+            use_cache: true,
+            path_filter: PathFilter::default(),
+            use_gitattributes: true,
+            detect_generated: true,
+            ignore_whitespace: false,
+            ignore_whitespace_change: false,
+            ignore_revs_file: None,
+            max_files_per_commit: None,
+            max_lines_per_commit: None,
+            mass_change_policy: MassChangePolicy::default(),
+            include_deleted: false,
+            granularity: Granularity::default(),
+            trend_samples: DEFAULT_TREND_SAMPLES,
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// Identifica as opções que mudam o resultado do diff de um commit; o cache só é
+    /// reaproveitado quando esta impressão digital não muda.
+    pub fn diff_fingerprint(&self) -> String {
+        format!(
+            "renames={:?};merges={:?};whitespace={},{};granularity={:?}",
+            self.rename_threshold,
+            self.merge_policy,
+            self.ignore_whitespace,
+            self.ignore_whitespace_change,
+            self.granularity
+        )
+    }
+}
+
+/// Número de threads padrão: uma por CPU disponível.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Churn e autores de uma função.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FunctionHistory {
+    pub churn: usize,
+    pub authors: HashSet<String>,
+}
+
+/// Commit amostrado para a tendência de complexidade.
+#[derive(Debug, Clone)]
+pub struct TrendSample {
+    pub commit: Oid,
+    pub date: DateTime<Utc>,
+    /// Posição do commit na história analisada, a partir do mais antigo.
+    pub position: usize,
+    /// Renomeações registradas até chegar ao commit: mapeiam os caminhos que os arquivos
+    /// tinham nele para os caminhos atuais.
+    renames: HashMap<PathBuf, PathBuf>,
+}
+
+impl TrendSample {
+    /// Caminhos que o arquivo hoje em `current` pode ter no commit: os nomes antigos que
+    /// levam a ele, em ordem, e por último o próprio `current`.
+    pub fn historical_paths(&self, current: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .renames
+            .keys()
+            .filter(|old| follow_renames(&self.renames, old) == current)
+            .cloned()
+            .collect();
+        paths.sort();
+        paths.push(current.to_path_buf());
+        paths
+    }
+}
+
+/// Segue as renomeações a partir de `path` até o caminho atual.
+fn follow_renames(renames: &HashMap<PathBuf, PathBuf>, path: &Path) -> PathBuf {
+    let mut current = path;
+    let mut seen = HashSet::new();
+    while let Some(next) = renames.get(current) {
+        if !seen.insert(current) {
+            break;
+        }
+        current = next;
+    }
+    current.to_path_buf()
+}
+
+#[derive(Default)]
 pub struct GitMetrics {
+    /// Commit cuja árvore foi analisada; é dele que a complexidade deve ser lida.
+    pub revision: Option<Oid>,
     pub churn: HashMap<PathBuf, usize>,
     pub authors: HashMap<PathBuf, HashSet<String>>,
+    /// Caminhos históricos mapeados para o caminho atual do arquivo.
+    pub renames: HashMap<PathBuf, PathBuf>,
+    /// Arquivos com histórico que foram deixados de fora da análise, e o motivo.
+    pub excluded: BTreeMap<PathBuf, ExclusionReason>,
+    /// Commits que passaram dos limites de mudança em massa, na ordem da caminhada.
+    pub mass_changes: Vec<MassChange>,
+    /// Mudanças em arquivos binários, que ficam fora do churn de linhas e do score.
+    pub binary_churn: HashMap<PathBuf, BinaryChurn>,
+    /// Arquivos com histórico que não existem na revisão analisada. Só é preenchido com
+    /// `include_deleted`; caso contrário eles são removidos das métricas.
+    pub deleted: HashSet<PathBuf>,
+    /// Histórico por função de cada arquivo; só é preenchido com [`Granularity::Function`].
+    pub functions: HashMap<PathBuf, HashMap<String, FunctionHistory>>,
+    /// Commits amostrados para a tendência de complexidade, do mais novo para o mais antigo.
+    pub trend_samples: Vec<TrendSample>,
 }
 
 impl GitMetrics {
     pub fn new() -> Self {
-        Self {
-            churn: HashMap::new(),
-            authors: HashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn add_change(&mut self, path: PathBuf, lines_changed: usize, author: String) {
+        let path = self.current_path(&path);
         *self.churn.entry(path.clone()).or_insert(0) += lines_changed;
-        self.authors
+        self.authors.entry(path).or_default().insert(author);
+    }
+
+    /// Registra uma mudança em um arquivo binário que alterou seu tamanho em `size_delta` bytes.
+    pub fn add_binary_change(&mut self, path: &Path, size_delta: i64) {
+        let path = self.current_path(path);
+        let entry = self
+            .binary_churn
+            .entry(path.clone())
+            .or_insert_with(|| BinaryChurn {
+                path,
+                ..BinaryChurn::default()
+            });
+        entry.changes += 1;
+        entry.size_delta += size_delta;
+        entry.bytes_changed += size_delta.unsigned_abs();
+    }
+
+    /// Soma `lines_changed` linhas à função `function` do arquivo, creditando `authors`.
+    pub fn add_function_change(
+        &mut self,
+        path: &Path,
+        function: &str,
+        lines_changed: usize,
+        authors: &[String],
+    ) {
+        let path = self.current_path(path);
+        let history = self
+            .functions
             .entry(path)
-            .or_insert_with(HashSet::new)
-            .insert(author);
+            .or_default()
+            .entry(function.to_string())
+            .or_default();
+        history.churn += lines_changed;
+        history.authors.extend(authors.iter().cloned());
+    }
+
+    /// Credita `author` em um arquivo sem somar churn.
+    pub fn add_author(&mut self, path: &Path, author: String) {
+        let path = self.current_path(path);
+        self.authors.entry(path).or_default().insert(author);
+    }
+
+    /// Retorna o caminho atual de um arquivo, seguindo as renomeações já registradas.
+    pub fn current_path(&self, path: &Path) -> PathBuf {
+        follow_renames(&self.renames, path)
+    }
+
+    /// Registra que `old` passou a se chamar `new`. Como o histórico é percorrido do commit
+    /// mais novo para o mais antigo, as mudanças anteriores em `old` passam a contar para o
+    /// caminho atual de `new`.
+    pub fn record_rename(&mut self, old: PathBuf, new: &Path) {
+        let target = self.current_path(new);
+        if old != target {
+            self.renames.insert(old, target);
+        }
+    }
+
+    /// Soma as mudanças de um commit. Renomeações e criações só valem para os commits mais
+    /// antigos que este, por isso são aplicadas depois das mudanças.
+    ///
+    /// O churn é contado uma única vez, e todos os `authors` (autor e coautores) são
+    /// creditados em cada arquivo alterado. Sem `authors` (commit ignorado), o commit só
+    /// contribui com as renomeações.
+    pub fn apply_commit(&mut self, changes: &CommitChanges, authors: &[String]) {
+        if let Some((author, co_authors)) = authors.split_first() {
+            for (path, churn) in &changes.files {
+                self.add_change(path.clone(), *churn, author.clone());
+                for co_author in co_authors {
+                    self.add_author(path, co_author.clone());
+                }
+            }
+            for (path, size_delta) in &changes.binaries {
+                self.add_binary_change(path, *size_delta);
+            }
+            for (path, function, churn) in &changes.functions {
+                self.add_function_change(path, function, *churn, authors);
+            }
+        }
+        for path in &changes.added {
+            self.forget_path(path);
+        }
+        for (old_path, new_path) in &changes.renamed {
+            self.record_rename(old_path.clone(), new_path);
+        }
+    }
+
+    /// Mantém apenas os arquivos para os quais `keep` retorna `true`.
+    pub fn retain_paths(&mut self, keep: impl Fn(&Path) -> bool) {
+        self.churn.retain(|path, _| keep(path));
+        self.authors.retain(|path, _| keep(path));
+        self.binary_churn.retain(|path, _| keep(path));
+        self.functions.retain(|path, _| keep(path));
+    }
+
+    /// Tira do churn de linhas os arquivos que foram binários em algum commit: o histórico
+    /// deles fica só em `binary_churn`.
+    pub fn separate_binaries(&mut self) {
+        for path in self.binary_churn.keys() {
+            self.churn.remove(path);
+            self.authors.remove(path);
+            self.functions.remove(path);
+        }
+    }
+
+    /// Remove da análise os arquivos (de texto ou binários) para os quais `reason_of` indica
+    /// um motivo de exclusão, registrando-os em `excluded`.
+    pub fn exclude_paths(&mut self, reason_of: impl Fn(&Path) -> Option<ExclusionReason>) {
+        let excluded: Vec<(PathBuf, ExclusionReason)> = self
+            .churn
+            .keys()
+            .chain(self.binary_churn.keys())
+            .filter_map(|path| reason_of(path).map(|reason| (path.clone(), reason)))
+            .collect();
+
+        for (path, reason) in excluded {
+            self.churn.remove(&path);
+            self.authors.remove(&path);
+            self.functions.remove(&path);
+            self.binary_churn.remove(&path);
+            self.excluded.insert(path, reason);
+        }
+    }
+
+    /// Esquece o mapeamento de `path`: o arquivo foi criado neste ponto do histórico, então
+    /// versões mais antigas com o mesmo caminho pertencem a outro arquivo.
+    pub fn forget_path(&mut self, path: &Path) {
+        self.renames.remove(path);
     }
 }
 
-pub fn analyze_repository(
-    repo_path: &PathBuf,
-    since: Option<&str>,
-    until: Option<&str>,
-) -> Result<GitMetrics> {
+pub fn analyze_repository(repo_path: &PathBuf, options: &AnalysisOptions) -> Result<GitMetrics> {
     let repo = Repository::open(repo_path).context("Falha ao abrir repositório")?;
 
-    let since_time = parse_date(since)?;
-    let until_time = parse_date(until)?;
+    let since_time = resolve_date(
+        &repo,
+        options.since.as_deref(),
+        DateBound::Start,
+        options.date_field,
+    )?;
+    let until_time = resolve_date(
+        &repo,
+        options.until.as_deref(),
+        DateBound::End,
+        options.date_field,
+    )?;
+
+    let identities =
+        IdentityResolver::new(&repo, options.alias_file.as_deref(), options.author_key)?
+            .with_filter(options.author_filter.clone());
+    let ignored_revs = match &options.ignore_revs_file {
+        Some(file) => read_ignore_revs(&repo, file)?,
+        None => HashSet::new(),
+    };
 
     let mut metrics = GitMetrics::new();
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    // Filhos antes dos pais: as renomeações precisam ser vistas antes do histórico antigo
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    push_revisions(&repo, &mut revwalk, options)?;
+    if options.merge_policy == MergePolicy::FirstParent {
+        revwalk.simplify_first_parent()?;
+    }
+    // O corte antecipado anda em ordem de data de commit; pela data de autoria, que o rebase
+    // não atualiza, um commit dentro do período pode estar atrás de vários fora dele
+    if let Some(since) = since_time.filter(|_| options.date_field == DateField::Committer) {
+        hide_commits_before(&repo, &mut revwalk, options, since)?;
+    }
+
+    // Caminhada sequencial e barata; os diffs vêm depois, possivelmente em paralelo
+    let mut commits = Vec::new();
+    let mut commit_authors = Vec::new();
 
     for oid in revwalk {
         let oid = oid?;
         let commit = repo.find_commit(oid)?;
 
-        let commit_time = commit.time();
-        let commit_datetime = Utc.timestamp_opt(commit_time.seconds(), 0).unwrap();
+        let commit_datetime = commit_datetime(&commit, options.date_field);
 
         if let Some(since) = since_time {
             if commit_datetime < since {
@@ -59,123 +437,2447 @@ pub fn analyze_repository(
             }
         }
 
-        let author = commit.author();
-        let author_name = author.name().unwrap_or("Unknown").to_string();
+        if commit.parent_count() > 1 && options.merge_policy == MergePolicy::Skip {
+            continue;
+        }
+
+        // Commits ignorados ou de autores filtrados continuam na caminhada, sem autores,
+        // para que suas renomeações valham
+        let authors = if ignored_revs.contains(&oid) {
+            Vec::new()
+        } else {
+            identities.commit_authors(&commit)
+        };
+
+        commits.push(oid);
+        commit_authors.push(authors);
+    }
 
-        let tree = commit.tree()?;
-        let parent_count = commit.parent_count();
+    let mut cache = options
+        .use_cache
+        .then(|| ChangeCache::open(repo.path(), options.diff_fingerprint()));
 
-        if parent_count == 0 {
-            continue;
+    // Só os commits fora do cache precisam de diff; a aplicação segue a ordem da caminhada
+    let missing: Vec<usize> = (0..commits.len())
+        .filter(|&i| cache.as_ref().is_none_or(|c| c.get(commits[i]).is_none()))
+        .collect();
+    let missing_oids: Vec<Oid> = missing.iter().map(|&i| commits[i]).collect();
+    let mut next_to_apply = 0;
+
+    let mut trend_samples = HashMap::new();
+    for index in sample_indices(commits.len(), options.trend_samples) {
+        let commit = repo.find_commit(commits[index])?;
+        trend_samples.insert(index, commit_datetime(&commit, options.date_field));
+    }
+
+    let apply = |metrics: &mut GitMetrics, index: usize, changes: &CommitChanges| {
+        // `--include`/`--exclude` valem antes de somar o commit (e de medir se ele é uma
+        // mudança em massa), pelo caminho atual de cada arquivo
+        let changes =
+            &changes.retain_paths(|path| options.path_filter.matches(&metrics.current_path(path)));
+
+        // A amostra vem antes das renomeações do próprio commit, que valem para os mais antigos
+        if let Some(&date) = trend_samples.get(&index) {
+            metrics.trend_samples.push(TrendSample {
+                commit: commits[index],
+                date,
+                position: commits.len() - 1 - index,
+                renames: metrics.renames.clone(),
+            });
         }
 
-        for i in 0..parent_count {
-            let parent = commit.parent(i)?;
-            let parent_tree = parent.tree()?;
+        let authors = &commit_authors[index];
+        let weight = match mass_change_weight(changes, options) {
+            Some(weight) if !authors.is_empty() => weight,
+            _ => return metrics.apply_commit(changes, authors),
+        };
 
-            let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+        metrics.mass_changes.push(MassChange {
+            commit: commits[index].to_string(),
+            summary: String::new(),
+            files: changes.files.len(),
+            lines: changes.total_churn(),
+        });
+        match options.mass_change_policy {
+            MassChangePolicy::Exclude => metrics.apply_commit(changes, &[]),
+            MassChangePolicy::Dampen => metrics.apply_commit(&changes.scaled(weight), authors),
+        }
+    };
 
-            // Obter estatísticas do diff
-            let stats = diff.stats()?;
-            let files_changed = stats.files_changed();
+    let apply_cached =
+        |metrics: &mut GitMetrics, cache: Option<&ChangeCache>, range: Range<usize>| {
+            for index in range {
+                if let Some(changes) = cache.and_then(|c| c.get(commits[index])) {
+                    apply(metrics, index, changes);
+                }
+            }
+        };
 
-            // Para cada arquivo, somar adições e deleções
-            for file_idx in 0..files_changed {
-                if let Some(delta) = diff.get_delta(file_idx) {
-                    if let Some(path) = delta.new_file().path() {
-                        // Obter o patch para contar linhas
-                        if let Ok(Some(patch)) = git2::Patch::from_diff(&diff, file_idx) {
-                            if let Ok((_, additions, deletions)) = patch.line_stats() {
-                                let churn = additions + deletions;
+    for_each_commit_changes(&repo, &missing_oids, options, |i, changes| {
+        let index = missing[i];
+        apply_cached(&mut metrics, cache.as_ref(), next_to_apply..index);
+        apply(&mut metrics, index, &changes);
+        next_to_apply = index + 1;
 
-                                if churn > 0 {
-                                    let path_buf = PathBuf::from(path);
-                                    metrics.add_change(path_buf, churn, author_name.clone());
-                                }
-                            }
-                        }
+        if let Some(cache) = cache.as_mut() {
+            cache.insert(commits[index], changes);
+        }
+    })?;
+    apply_cached(&mut metrics, cache.as_ref(), next_to_apply..commits.len());
+
+    if let Some(cache) = cache {
+        if let Err(err) = cache.save() {
+            eprintln!("Aviso: falha ao gravar o cache de análise: {}", err);
+        }
+    }
+
+    for mass_change in &mut metrics.mass_changes {
+        let commit = repo.find_commit(Oid::from_str(&mass_change.commit)?)?;
+        mass_change.summary = commit.summary().unwrap_or("").to_string();
+    }
+
+    metrics.separate_binaries();
+    metrics.retain_paths(|path| options.path_filter.matches(path));
+
+    let revision = target_commit(&repo, options)?;
+    let tree = repo.find_commit(revision)?.tree()?;
+    metrics.revision = Some(revision);
+
+    metrics.deleted = metrics
+        .churn
+        .keys()
+        .chain(metrics.binary_churn.keys())
+        .filter(|path| tree.get_path(path).is_err())
+        .cloned()
+        .collect();
+    if !options.include_deleted {
+        let deleted = std::mem::take(&mut metrics.deleted);
+        metrics.retain_paths(|path| !deleted.contains(path));
+    }
+
+    if options.use_gitattributes {
+        let attributes = GitAttributes::from_tree(&repo, &tree)
+            .context("Falha ao ler o .gitattributes da revisão analisada")?;
+        metrics.exclude_paths(|path| attributes.exclusion_reason(path));
+    }
+
+    if options.detect_generated {
+        metrics.exclude_paths(|path| {
+            let blob = tree
+                .get_path(path)
+                .ok()
+                .and_then(|entry| repo.find_blob(entry.id()).ok());
+            generated::classify(path, blob.as_ref().map(|b| b.content()))
+        });
+    }
+
+    Ok(metrics)
+}
+
+/// Índices de `samples` posições espaçadas por igual em uma lista de `len` commits, incluindo
+/// a primeira e a última.
+fn sample_indices(len: usize, samples: usize) -> Vec<usize> {
+    match samples {
+        0 => Vec::new(),
+        _ if samples >= len => (0..len).collect(),
+        1 => vec![0],
+        _ => (0..samples)
+            .map(|i| i * (len - 1) / (samples - 1))
+            .collect(),
+    }
+}
+
+/// Calcula as mudanças de cada commit de `commits` e as entrega a `apply` na mesma ordem da
+/// lista, para que o resultado não dependa do agendamento das threads.
+///
+/// Com `options.jobs > 1`, os diffs são calculados por threads que abrem seus próprios
+/// [`Repository`] (o handle do libgit2 não pode ser compartilhado entre threads) e pegam o
+/// próximo commit da lista conforme ficam livres.
+fn for_each_commit_changes(
+    repo: &Repository,
+    commits: &[Oid],
+    options: &AnalysisOptions,
+    mut apply: impl FnMut(usize, CommitChanges),
+) -> Result<()> {
+    let jobs = options.jobs.clamp(1, commits.len().max(1));
+
+    if jobs == 1 {
+        for (index, oid) in commits.iter().enumerate() {
+            let commit = repo.find_commit(*oid)?;
+            apply(index, commit_changes(repo, &commit, options)?);
+        }
+        return Ok(());
+    }
+
+    let git_dir = repo.path();
+    let next_index = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::sync_channel::<(usize, Result<CommitChanges>)>(jobs * 4);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let next_index = &next_index;
+            scope.spawn(move || {
+                let repo = match Repository::open(git_dir) {
+                    Ok(repo) => repo,
+                    Err(err) => {
+                        let _ = sender.send((0, Err(err.into())));
+                        return;
+                    }
+                };
+
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(oid) = commits.get(index) else {
+                        break;
+                    };
+                    let changes = repo
+                        .find_commit(*oid)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|commit| commit_changes(&repo, &commit, options));
+
+                    // O receptor só desiste quando já houve um erro
+                    if sender.send((index, changes)).is_err() {
+                        break;
                     }
                 }
+            });
+        }
+        drop(sender);
+
+        // Resultados fora de ordem esperam aqui até chegar a vez deles
+        let mut waiting = BTreeMap::new();
+        let mut next_to_apply = 0;
+
+        for (index, changes) in receiver {
+            waiting.insert(index, changes?);
+            while let Some(changes) = waiting.remove(&next_to_apply) {
+                apply(next_to_apply, changes);
+                next_to_apply += 1;
             }
         }
+
+        Ok(())
+    })
+}
+
+/// Define os pontos de partida da caminhada: as revisões e intervalos pedidos, todos os
+/// ramos locais com `all_branches`, ou `HEAD` quando nada foi informado.
+fn push_revisions(
+    repo: &Repository,
+    revwalk: &mut Revwalk,
+    options: &AnalysisOptions,
+) -> Result<()> {
+    if options.all_branches {
+        revwalk.push_glob("refs/heads/*")?;
     }
 
-    Ok(metrics)
+    for spec in &options.revisions {
+        let revspec = repo
+            .revparse(spec)
+            .with_context(|| format!("Revisão inválida: {}", spec))?;
+        let commit_of = |object: Option<&git2::Object>| -> Result<Oid> {
+            let object = object.with_context(|| format!("Revisão inválida: {}", spec))?;
+            Ok(object.peel_to_commit()?.id())
+        };
+
+        if revspec.mode().contains(RevparseMode::SINGLE) {
+            revwalk.push(commit_of(revspec.from())?)?;
+        } else if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+            // A...B: commits alcançáveis por apenas um dos lados
+            let from = commit_of(revspec.from())?;
+            let to = commit_of(revspec.to())?;
+            revwalk.push(from)?;
+            revwalk.push(to)?;
+            revwalk.hide(repo.merge_base(from, to)?)?;
+        } else {
+            revwalk.push(commit_of(revspec.to())?)?;
+            revwalk.hide(commit_of(revspec.from())?)?;
+        }
+    }
+
+    if !options.all_branches && options.revisions.is_empty() {
+        revwalk.push_head()?;
+    }
+
+    Ok(())
 }
 
-fn parse_date(date_str: Option<&str>) -> Result<Option<DateTime<Utc>>> {
-    match date_str {
-        None => Ok(None),
-        Some(s) => {
-            let parsed = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
-                .context("Data inválida, use formato YYYY-MM-DD")?
-                .and_hms_opt(0, 0, 0)
-                .unwrap();
-            Ok(Some(DateTime::from_naive_utc_and_offset(parsed, Utc)))
+/// Lê os commits listados em um arquivo no formato do `.git-blame-ignore-revs`: um hash
+/// (completo ou abreviado) por linha, com comentários iniciados por `#`.
+fn read_ignore_revs(repo: &Repository, file: &Path) -> Result<HashSet<Oid>> {
+    let path = match repo.workdir() {
+        Some(workdir) if file.is_relative() => workdir.join(file),
+        _ => file.to_path_buf(),
+    };
+    let content = std::fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Falha ao ler arquivo de commits ignorados {}",
+            path.display()
+        )
+    })?;
+
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|rev| {
+            let commit = repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .with_context(|| format!("Commit inválido em {}: {}", path.display(), rev))?;
+            Ok(commit.id())
+        })
+        .collect()
+}
+
+/// Commit cuja árvore representa o estado analisado: a primeira revisão pedida (o lado
+/// final, se for um intervalo) ou `HEAD`.
+fn target_commit(repo: &Repository, options: &AnalysisOptions) -> Result<Oid> {
+    let Some(spec) = options.revisions.first() else {
+        return Ok(repo.head()?.peel_to_commit()?.id());
+    };
+
+    let revspec = repo
+        .revparse(spec)
+        .with_context(|| format!("Revisão inválida: {}", spec))?;
+    let object = if revspec.mode().contains(RevparseMode::SINGLE) {
+        revspec.from()
+    } else {
+        revspec.to()
+    };
+
+    let object = object.with_context(|| format!("Revisão inválida: {}", spec))?;
+    Ok(object.peel_to_commit()?.id())
+}
+
+/// Quantos commits seguidos anteriores a `--since` são tolerados antes de encerrar a busca
+/// pela fronteira, como o `git log` faz para lidar com datas fora de ordem.
+const SINCE_SLOP: usize = 5;
+
+/// Esconde de `revwalk` os ancestrais dos commits anteriores a `since`, para que a caminhada
+/// não precise ir até a raiz.
+///
+/// Uma caminhada auxiliar em ordem de data procura a fronteira. Um commit antigo isolado no
+/// meio de commits recentes (relógio fora de ordem) não encerra a busca, e seus ancestrais
+/// continuam sendo visitados; ela só para depois de [`SINCE_SLOP`] commits antigos seguidos.
+/// Nesse ponto, tudo o que ainda está na fila é mais antigo que o último commit visitado e é
+/// escondido junto com seus ancestrais.
+///
+/// Como a caminhada auxiliar segue a data de commit, o corte só é seguro com
+/// [`DateField::Committer`].
+fn hide_commits_before(
+    repo: &Repository,
+    revwalk: &mut Revwalk,
+    options: &AnalysisOptions,
+    since: DateTime<Utc>,
+) -> Result<()> {
+    let first_parent = options.merge_policy == MergePolicy::FirstParent;
+
+    let mut probe = repo.revwalk()?;
+    probe.set_sorting(Sort::TIME)?;
+    push_revisions(repo, &mut probe, options)?;
+    if first_parent {
+        probe.simplify_first_parent()?;
+    }
+
+    // Pais de commits já visitados que ainda não saíram da fila da caminhada auxiliar
+    let mut pending = HashSet::new();
+    let mut old_in_a_row = 0;
+
+    for oid in probe {
+        let commit = repo.find_commit(oid?)?;
+        pending.remove(&commit.id());
+        let parents = commit
+            .parent_ids()
+            .take(if first_parent { 1 } else { usize::MAX });
+        pending.extend(parents);
+
+        if commit_datetime(&commit, DateField::Committer) >= since {
+            old_in_a_row = 0;
+            continue;
+        }
+
+        old_in_a_row += 1;
+        if old_in_a_row >= SINCE_SLOP {
+            for oid in pending {
+                revwalk.hide(oid)?;
+            }
+            break;
         }
     }
+
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn commit_datetime(commit: &git2::Commit, date_field: DateField) -> DateTime<Utc> {
+    let time = match date_field {
+        DateField::Author => commit.author().when(),
+        DateField::Committer => commit.time(),
+    };
+    Utc.timestamp_opt(time.seconds(), 0).unwrap()
+}
 
-    #[test]
-    fn test_git_metrics_new() {
-        let metrics = GitMetrics::new();
-        assert!(metrics.churn.is_empty());
-        assert!(metrics.authors.is_empty());
+/// Mudanças de um commit já reduzidas conforme a política de merge.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CommitChanges {
+    /// Arquivos alterados e o número de linhas adicionadas + removidas.
+    pub files: Vec<(PathBuf, usize)>,
+    /// Pares (caminho antigo, caminho novo) renomeados no commit.
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    /// Arquivos que não existiam em nenhum dos pais.
+    pub added: Vec<PathBuf>,
+    /// Arquivos binários alterados e a variação do tamanho em bytes.
+    pub binaries: Vec<(PathBuf, i64)>,
+    /// Linhas alteradas por função (caminho, função, linhas), com [`Granularity::Function`].
+    pub functions: Vec<(PathBuf, String, usize)>,
+}
+
+impl CommitChanges {
+    /// Total de linhas alteradas no commit.
+    pub fn total_churn(&self) -> usize {
+        self.files.iter().map(|(_, churn)| churn).sum()
     }
 
-    #[test]
-    fn test_git_metrics_add_change() {
-        let mut metrics = GitMetrics::new();
-        let path = PathBuf::from("src/main.rs");
-        metrics.add_change(path.clone(), 10, "Alice".to_string());
+    /// Cópia só com as mudanças nos arquivos para os quais `keep` retorna `true`. Renomeações e
+    /// criações são mantidas, porque continuam valendo para seguir o histórico.
+    pub fn retain_paths(&self, keep: impl Fn(&Path) -> bool) -> CommitChanges {
+        CommitChanges {
+            files: self
+                .files
+                .iter()
+                .filter(|(path, _)| keep(path))
+                .cloned()
+                .collect(),
+            binaries: self
+                .binaries
+                .iter()
+                .filter(|(path, _)| keep(path))
+                .cloned()
+                .collect(),
+            functions: self
+                .functions
+                .iter()
+                .filter(|(path, _, _)| keep(path))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
 
-        assert_eq!(*metrics.churn.get(&path).unwrap(), 10);
-        assert_eq!(metrics.authors.get(&path).unwrap().len(), 1);
+    /// Cópia com o churn de cada arquivo multiplicado por `weight`, arredondado para cima
+    /// para que nenhum arquivo alterado fique com churn zero.
+    pub fn scaled(&self, weight: f64) -> CommitChanges {
+        let scale = |churn: usize| (churn as f64 * weight).ceil() as usize;
+
+        CommitChanges {
+            files: self
+                .files
+                .iter()
+                .map(|(path, churn)| (path.clone(), scale(*churn)))
+                .collect(),
+            functions: self
+                .functions
+                .iter()
+                .map(|(path, function, churn)| (path.clone(), function.clone(), scale(*churn)))
+                .collect(),
+            ..self.clone()
+        }
     }
+}
 
-    #[test]
-    fn test_git_metrics_add_multiple_changes_same_file() {
-        let mut metrics = GitMetrics::new();
-        let path = PathBuf::from("src/main.rs");
+/// Se o commit passa de algum limite de mudança em massa, retorna o peso que traz o commit
+/// de volta ao limite mais violado (`limite / valor`, entre 0 e 1).
+fn mass_change_weight(changes: &CommitChanges, options: &AnalysisOptions) -> Option<f64> {
+    let ratio = |limit: Option<usize>, value: usize| {
+        limit
+            .filter(|&limit| value > limit)
+            .map(|limit| limit as f64 / value as f64)
+    };
 
-        metrics.add_change(path.clone(), 10, "Alice".to_string());
-        metrics.add_change(path.clone(), 5, "Bob".to_string());
+    let by_files = ratio(options.max_files_per_commit, changes.files.len());
+    let by_lines = ratio(options.max_lines_per_commit, changes.total_churn());
 
-        assert_eq!(*metrics.churn.get(&path).unwrap(), 15);
-        assert_eq!(metrics.authors.get(&path).unwrap().len(), 2);
+    match (by_files, by_lines) {
+        (Some(files), Some(lines)) => Some(files.min(lines)),
+        (weight, None) | (None, weight) => weight,
     }
+}
 
-    #[test]
-    fn test_git_metrics_multiple_authors_same_file() {
-        let mut metrics = GitMetrics::new();
-        let path = PathBuf::from("src/main.rs");
+struct FileChange {
+    path: PathBuf,
+    old_path: Option<PathBuf>,
+    /// Blob do arquivo no commit.
+    new_id: Oid,
+    status: Delta,
+    churn: usize,
+    /// Variação do tamanho em bytes, se o arquivo é binário.
+    binary_size_delta: Option<i64>,
+    /// Linhas alteradas por função, com [`Granularity::Function`].
+    functions: Vec<(String, usize)>,
+    /// Linhas alteradas, só nos diffs de merges.
+    lines: Option<LineChanges>,
+}
 
-        metrics.add_change(path.clone(), 5, "Alice".to_string());
-        metrics.add_change(path.clone(), 3, "Bob".to_string());
-        metrics.add_change(path.clone(), 2, "Charlie".to_string());
+/// Linhas alteradas por um diff, na numeração do arquivo novo, para combinar os diffs de um
+/// merge contra cada pai.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct LineChanges {
+    /// Linhas do arquivo novo que foram adicionadas.
+    added: BTreeSet<u32>,
+    /// Quantas linhas foram removidas logo depois de cada linha do arquivo novo (0 é o
+    /// início do arquivo).
+    removed: BTreeMap<u32, usize>,
+}
 
-        assert_eq!(metrics.authors.get(&path).unwrap().len(), 3);
+impl LineChanges {
+    /// Linhas alteradas em relação a todos os pais ao mesmo tempo, como no `git diff --cc`:
+    /// adições que não existem em nenhum pai e remoções feitas no mesmo ponto do arquivo
+    /// contra todos eles.
+    fn combined<'a>(mut diffs: impl Iterator<Item = &'a LineChanges>) -> LineChanges {
+        let Some(first) = diffs.next() else {
+            return LineChanges::default();
+        };
+        diffs.fold(first.clone(), |combined, other| LineChanges {
+            added: combined.added.intersection(&other.added).copied().collect(),
+            removed: combined
+                .removed
+                .iter()
+                .filter_map(|(anchor, count)| {
+                    let common = (*count).min(*other.removed.get(anchor)?);
+                    Some((*anchor, common))
+                })
+                .collect(),
+        })
     }
 
-    #[test]
-    fn test_parse_date_none() {
-        let result = parse_date(None).unwrap();
-        assert!(result.is_none());
+    fn churn(&self) -> usize {
+        self.added.len() + self.removed.values().sum::<usize>()
     }
 
-    #[test]
-    fn test_parse_date_valid() {
-        let result = parse_date(Some("2024-01-15")).unwrap();
-        assert!(result.is_some());
+    fn from_patch(patch: &git2::Patch) -> Result<LineChanges> {
+        let mut lines = LineChanges::default();
+
+        for hunk in 0..patch.num_hunks() {
+            let (header, _) = patch.hunk(hunk)?;
+            // Última linha do arquivo novo antes do ponto atual do hunk
+            let mut anchor = if header.new_lines() == 0 {
+                header.new_start()
+            } else {
+                header.new_start() - 1
+            };
+
+            for line in 0..patch.num_lines_in_hunk(hunk)? {
+                let line = patch.line_in_hunk(hunk, line)?;
+                match line.origin() {
+                    '-' => *lines.removed.entry(anchor).or_insert(0) += 1,
+                    '+' => {
+                        if let Some(lineno) = line.new_lineno() {
+                            lines.added.insert(lineno);
+                            anchor = lineno;
+                        }
+                    }
+                    _ => anchor = line.new_lineno().unwrap_or(anchor),
+                }
+            }
+        }
+
+        Ok(lines)
+    }
+}
+
+/// Calcula as mudanças de um commit em relação aos pais relevantes para `options.merge_policy`.
+///
+/// Em merges com [`MergePolicy::Conflicts`], um arquivo só conta se difere de todos os pais,
+/// e só contam as linhas que diferem de todos eles (como no `git diff --cc`): o que veio de
+/// um dos ramos já foi contado nos commits desse ramo.
+fn commit_changes(
+    repo: &Repository,
+    commit: &git2::Commit,
+    options: &AnalysisOptions,
+) -> Result<CommitChanges> {
+    let parents_to_diff = match options.merge_policy {
+        MergePolicy::FirstParent => commit.parent_count().min(1),
+        _ => commit.parent_count(),
+    };
+
+    let tree = commit.tree()?;
+    let mut diffs = Vec::with_capacity(parents_to_diff);
+    let is_merge = parents_to_diff > 1;
+    for i in 0..parents_to_diff {
+        let parent_tree = commit.parent(i)?.tree()?;
+        diffs.push(diff_trees(repo, &parent_tree, &tree, options, is_merge)?);
+    }
+
+    let mut changes = CommitChanges::default();
+    let Some((first, others)) = diffs.split_first() else {
+        return Ok(changes);
+    };
+
+    for diff in &diffs {
+        for change in diff {
+            if change.status == Delta::Renamed {
+                if let Some(old_path) = &change.old_path {
+                    let pair = (old_path.clone(), change.path.clone());
+                    if !changes.renamed.contains(&pair) {
+                        changes.renamed.push(pair);
+                    }
+                }
+            }
+        }
+    }
+
+    let others: Vec<HashMap<&Path, &FileChange>> = others
+        .iter()
+        .map(|diff| diff.iter().map(|c| (c.path.as_path(), c)).collect())
+        .collect();
+
+    for change in first {
+        let in_others: Option<Vec<&FileChange>> = others
+            .iter()
+            .map(|diff| diff.get(change.path.as_path()).copied())
+            .collect();
+
+        // Ausente em algum diff: o arquivo é idêntico ao de um dos pais
+        let Some(in_others) = in_others else {
+            continue;
+        };
+
+        if let Some(size_delta) = change.binary_size_delta {
+            changes.binaries.push((change.path.clone(), size_delta));
+        } else if !is_merge {
+            if change.churn > 0 {
+                changes.files.push((change.path.clone(), change.churn));
+                for (function, churn) in &change.functions {
+                    changes
+                        .functions
+                        .push((change.path.clone(), function.clone(), *churn));
+                }
+            }
+        } else {
+            let lines = LineChanges::combined(
+                std::iter::once(change)
+                    .chain(in_others.iter().copied())
+                    .filter_map(|c| c.lines.as_ref()),
+            );
+            if lines.churn() > 0 {
+                changes.files.push((change.path.clone(), lines.churn()));
+                if options.granularity == Granularity::Function {
+                    for (function, churn) in
+                        merge_function_churn(repo, &change.path, change.new_id, &lines)?
+                    {
+                        changes
+                            .functions
+                            .push((change.path.clone(), function, churn));
+                    }
+                }
+            }
+        }
+
+        if change.status == Delta::Added && in_others.iter().all(|o| o.status == Delta::Added) {
+            changes.added.push(change.path.clone());
+        }
+    }
+
+    Ok(changes)
+}
+
+fn diff_trees(
+    repo: &Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    options: &AnalysisOptions,
+    with_lines: bool,
+) -> Result<Vec<FileChange>> {
+    let mut diff_opts = DiffOptions::new();
+    diff_opts
+        .ignore_whitespace(options.ignore_whitespace)
+        .ignore_whitespace_change(options.ignore_whitespace_change);
+    let mut diff = repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(&mut diff_opts))?;
+
+    if let Some(threshold) = options.rename_threshold {
+        let mut find_opts = DiffFindOptions::new();
+        find_opts.renames(true).rename_threshold(threshold);
+        diff.find_similar(Some(&mut find_opts))?;
+    }
+
+    let mut changes = Vec::new();
+
+    // Para cada arquivo, somar adições e deleções
+    for file_idx in 0..diff.deltas().len() {
+        let Some(delta) = diff.get_delta(file_idx) else {
+            continue;
+        };
+        let Some(path) = delta.new_file().path() else {
+            continue;
+        };
+
+        // Obter o patch para contar linhas; gerar o patch também carrega o conteúdo e
+        // decide se o arquivo é binário
+        let patch = git2::Patch::from_diff(&diff, file_idx).ok().flatten();
+        let churn = patch
+            .as_ref()
+            .and_then(|patch| patch.line_stats().ok())
+            .map_or(0, |(_, additions, deletions)| additions + deletions);
+        let is_binary = patch.as_ref().map_or(delta.flags().is_binary(), |patch| {
+            patch.delta().flags().is_binary()
+        });
+
+        // Um binário só renomeado não teve mudança de conteúdo
+        let content_changed = delta.old_file().id() != delta.new_file().id();
+        let binary_size_delta = if is_binary && content_changed {
+            let size = |id: Oid| -> Result<i64> {
+                if id.is_zero() {
+                    return Ok(0);
+                }
+                Ok(repo.find_blob(id)?.size() as i64)
+            };
+            Some(size(delta.new_file().id())? - size(delta.old_file().id())?)
+        } else {
+            None
+        };
+
+        let functions = match &patch {
+            Some(patch) if !is_binary && options.granularity == Granularity::Function => {
+                function_churn(repo, patch)?
+            }
+            _ => Vec::new(),
+        };
+        let lines = match &patch {
+            Some(patch) if with_lines && !is_binary => Some(LineChanges::from_patch(patch)?),
+            _ => None,
+        };
+
+        changes.push(FileChange {
+            path: path.to_path_buf(),
+            old_path: delta.old_file().path().map(Path::to_path_buf),
+            new_id: delta.new_file().id(),
+            status: delta.status(),
+            churn,
+            binary_size_delta,
+            functions,
+            lines,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Distribui as linhas de um patch entre as funções que as contêm: linhas adicionadas pelas
+/// funções da versão nova do arquivo, removidas pelas da versão antiga. Linhas fora de
+/// funções e arquivos de linguagens sem análise sintática não contam.
+fn function_churn(repo: &Repository, patch: &git2::Patch) -> Result<Vec<(String, usize)>> {
+    let delta = patch.delta();
+    let Some(language) = delta.new_file().path().and_then(Language::from_path) else {
+        return Ok(Vec::new());
+    };
+
+    let old_functions = functions_of(repo, language, delta.old_file().id())?;
+    let new_functions = functions_of(repo, language, delta.new_file().id())?;
+
+    let mut churn: BTreeMap<String, usize> = BTreeMap::new();
+    for hunk in 0..patch.num_hunks() {
+        for line in 0..patch.num_lines_in_hunk(hunk)? {
+            let line = patch.line_in_hunk(hunk, line)?;
+            let function = match line.origin() {
+                '+' => line
+                    .new_lineno()
+                    .and_then(|n| syntax::function_at_line(&new_functions, n as usize)),
+                '-' => line
+                    .old_lineno()
+                    .and_then(|n| syntax::function_at_line(&old_functions, n as usize)),
+                _ => None,
+            };
+            if let Some(function) = function {
+                *churn.entry(function.name.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    Ok(churn.into_iter().collect())
+}
+
+/// Distribui as linhas alteradas por um merge entre as funções do arquivo resultante. As
+/// remoções contam para a função em que estavam, ou para a seguinte.
+fn merge_function_churn(
+    repo: &Repository,
+    path: &Path,
+    id: Oid,
+    lines: &LineChanges,
+) -> Result<Vec<(String, usize)>> {
+    let Some(language) = Language::from_path(path) else {
+        return Ok(Vec::new());
+    };
+    let functions = functions_of(repo, language, id)?;
+
+    let added = lines.added.iter().map(|&line| (line, 1));
+    let removed = lines
+        .removed
+        .iter()
+        .map(|(&anchor, &count)| (anchor, count));
+
+    let mut churn: BTreeMap<String, usize> = BTreeMap::new();
+    for (line, count) in added.chain(removed) {
+        let function = syntax::function_at_line(&functions, line as usize)
+            .or_else(|| syntax::function_at_line(&functions, line as usize + 1));
+        if let Some(function) = function {
+            *churn.entry(function.name.clone()).or_insert(0) += count;
+        }
+    }
+
+    Ok(churn.into_iter().collect())
+}
+
+/// Funções do blob `id`; nenhuma se o arquivo não existe.
+fn functions_of(repo: &Repository, language: Language, id: Oid) -> Result<Vec<Function>> {
+    if id.is_zero() {
+        return Ok(Vec::new());
+    }
+    let blob = repo.find_blob(id)?;
+    let source = String::from_utf8_lossy(blob.content());
+    Ok(SourceCode::parse(language, &source).functions)
+}
+
+/// Interpreta `--since`/`--until` como data, aceitando também uma revisão (`v1.2.0`,
+/// `main~10`), que vale pela data do commit correspondente.
+fn resolve_date(
+    repo: &Repository,
+    date_str: Option<&str>,
+    bound: DateBound,
+    date_field: DateField,
+) -> Result<Option<DateTime<Utc>>> {
+    match date_str {
+        None => Ok(None),
+        Some(s) => match parse_date(Some(s), bound) {
+            Ok(date) => Ok(date),
+            Err(date_err) => {
+                let commit = repo
+                    .revparse_single(s)
+                    .and_then(|object| object.peel_to_commit())
+                    .map_err(|_| date_err)?;
+                Ok(Some(commit_datetime(&commit, date_field)))
+            }
+        },
+    }
+}
+
+const DATE_FORMATS_HINT: &str =
+    "Use YYYY-MM-DD, RFC 3339, uma data relativa (\"6 months ago\", 2w) ou uma revisão";
+
+fn parse_date(date_str: Option<&str>, bound: DateBound) -> Result<Option<DateTime<Utc>>> {
+    match date_str {
+        None => Ok(None),
+        Some(s) => {
+            let parsed = dates::parse_date_expression(s, bound, Utc::now(), &Local)
+                .with_context(|| format!("Data inválida: {}. {}", s, DATE_FORMATS_HINT))?;
+            Ok(Some(parsed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::commit_snapshot;
+    use git2::{Signature, Time};
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Cria um commit em `HEAD` aplicando as mudanças dadas (`None` remove o arquivo).
+    fn commit_files(
+        repo: &Repository,
+        files: &[(&str, Option<&str>)],
+        author: &str,
+        time: i64,
+    ) -> Oid {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let mut index = repo.index().unwrap();
+
+        for (path, content) in files {
+            let full_path = workdir.join(path);
+            match content {
+                Some(content) => {
+                    fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+                    fs::write(&full_path, content).unwrap();
+                    index.add_path(Path::new(path)).unwrap();
+                }
+                None => {
+                    fs::remove_file(&full_path).unwrap();
+                    index.remove_path(Path::new(path)).unwrap();
+                }
+            }
+        }
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::new(author, "dev@example.com", &Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "commit",
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    /// Histórico com um ramo lateral mesclado: `a.txt` muda no ramo principal, `b.txt` no
+    /// ramo lateral, e o merge acrescenta uma linha própria em `a.txt`.
+    fn repo_with_merge() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let a = numbered_lines(10);
+        let b = numbered_lines(10);
+        let a_main = format!("{}principal\n", a);
+        let b_side = format!("{}lateral\n", b);
+        let a_merged = format!("{}resolvido\n", a_main);
+
+        let base = commit_snapshot(
+            &repo,
+            &[("a.txt", &a), ("b.txt", &b)],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
+        let main = commit_snapshot(
+            &repo,
+            &[("a.txt", &a_main), ("b.txt", &b)],
+            &[base],
+            "Alice",
+            1_700_000_100,
+        );
+        let side = commit_snapshot(
+            &repo,
+            &[("a.txt", &a), ("b.txt", &b_side)],
+            &[base],
+            "Bob",
+            1_700_000_200,
+        );
+        let merge = commit_snapshot(
+            &repo,
+            &[("a.txt", &a_merged), ("b.txt", &b_side)],
+            &[main, side],
+            "Alice",
+            1_700_000_300,
+        );
+        repo.set_head_detached(merge).unwrap();
+
+        temp_dir
+    }
+
+    fn analyze_with_merge_policy(repo_path: &Path, merge_policy: MergePolicy) -> GitMetrics {
+        let options = AnalysisOptions {
+            merge_policy,
+            ..AnalysisOptions::default()
+        };
+        analyze_repository(&repo_path.to_path_buf(), &options).unwrap()
+    }
+
+    fn numbered_lines(count: usize) -> String {
+        (0..count).map(|i| format!("linha {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_git_metrics_new() {
+        let metrics = GitMetrics::new();
+        assert!(metrics.churn.is_empty());
+        assert!(metrics.authors.is_empty());
+    }
+
+    #[test]
+    fn test_git_metrics_add_change() {
+        let mut metrics = GitMetrics::new();
+        let path = PathBuf::from("src/main.rs");
+        metrics.add_change(path.clone(), 10, "Alice".to_string());
+
+        assert_eq!(*metrics.churn.get(&path).unwrap(), 10);
+        assert_eq!(metrics.authors.get(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_git_metrics_add_multiple_changes_same_file() {
+        let mut metrics = GitMetrics::new();
+        let path = PathBuf::from("src/main.rs");
+
+        metrics.add_change(path.clone(), 10, "Alice".to_string());
+        metrics.add_change(path.clone(), 5, "Bob".to_string());
+
+        assert_eq!(*metrics.churn.get(&path).unwrap(), 15);
+        assert_eq!(metrics.authors.get(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_git_metrics_multiple_authors_same_file() {
+        let mut metrics = GitMetrics::new();
+        let path = PathBuf::from("src/main.rs");
+
+        metrics.add_change(path.clone(), 5, "Alice".to_string());
+        metrics.add_change(path.clone(), 3, "Bob".to_string());
+        metrics.add_change(path.clone(), 2, "Charlie".to_string());
+
+        assert_eq!(metrics.authors.get(&path).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_git_metrics_rename_folds_into_current_path() {
+        let mut metrics = GitMetrics::new();
+        let old = PathBuf::from("src/foo.rs");
+        let new = PathBuf::from("src/core/foo.rs");
+
+        metrics.add_change(new.clone(), 5, "Alice".to_string());
+        metrics.record_rename(old.clone(), &new);
+        metrics.add_change(old.clone(), 10, "Bob".to_string());
+
+        assert_eq!(*metrics.churn.get(&new).unwrap(), 15);
+        assert_eq!(metrics.authors.get(&new).unwrap().len(), 2);
+        assert!(!metrics.churn.contains_key(&old));
+    }
+
+    #[test]
+    fn test_git_metrics_rename_chain() {
+        let mut metrics = GitMetrics::new();
+        let a = PathBuf::from("a.rs");
+        let b = PathBuf::from("b.rs");
+        let c = PathBuf::from("c.rs");
+
+        metrics.record_rename(b.clone(), &c);
+        metrics.record_rename(a.clone(), &b);
+        metrics.add_change(a, 3, "Alice".to_string());
+
+        assert_eq!(*metrics.churn.get(&c).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_git_metrics_forget_path_stops_folding() {
+        let mut metrics = GitMetrics::new();
+        let old = PathBuf::from("old.rs");
+
+        metrics.record_rename(old.clone(), Path::new("new.rs"));
+        metrics.forget_path(&old);
+        metrics.add_change(old.clone(), 4, "Alice".to_string());
+
+        assert_eq!(*metrics.churn.get(&old).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_analyze_repository_follows_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", Some(&content))],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", None), ("src/core/foo.rs", Some(&content))],
+            "Bob",
+            1_700_000_200,
+        );
+        commit_files(
+            &repo,
+            &[("src/core/foo.rs", Some(&format!("{}nova\n", content)))],
+            "Bob",
+            1_700_000_300,
+        );
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+
+        let current = PathBuf::from("src/core/foo.rs");
+        assert!(!metrics.churn.contains_key(&PathBuf::from("src/foo.rs")));
+        assert_eq!(*metrics.churn.get(&current).unwrap(), 21);
+        assert_eq!(metrics.authors.get(&current).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_copied_file_counts_as_new_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("original.rs", Some(&content))],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("original.rs", Some(&format!("{}fim\n", content))),
+                ("copia.rs", Some(&format!("{}extra\n", content))),
+            ],
+            "Bob",
+            1_700_000_200,
+        );
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+
+        // A cópia conta o arquivo inteiro e não herda o histórico do original
+        assert_eq!(metrics.churn[&PathBuf::from("copia.rs")], 21);
+        assert_eq!(metrics.authors[&PathBuf::from("copia.rs")].len(), 1);
+        assert_eq!(metrics.churn[&PathBuf::from("original.rs")], 21);
+        assert!(metrics.renames.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_repository_without_rename_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", Some(&content))],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", None), ("src/core/foo.rs", Some(&content))],
+            "Bob",
+            1_700_000_200,
+        );
+
+        let options = AnalysisOptions {
+            rename_threshold: None,
+            include_deleted: true,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert_eq!(
+            *metrics.churn.get(&PathBuf::from("src/foo.rs")).unwrap(),
+            40
+        );
+        assert_eq!(
+            *metrics
+                .churn
+                .get(&PathBuf::from("src/core/foo.rs"))
+                .unwrap(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_merge_policy_conflicts_counts_only_resolution() {
+        let temp_dir = repo_with_merge();
+        let metrics = analyze_with_merge_policy(temp_dir.path(), MergePolicy::Conflicts);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("a.txt")).unwrap(), 2);
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_merge_policy_conflicts_clean_merge_of_same_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let lines: Vec<String> = (0..20).map(|i| format!("linha {}\n", i)).collect();
+        let edit = |edits: &[usize]| -> String {
+            lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    if edits.contains(&i) {
+                        format!("editada {}\n", i)
+                    } else {
+                        line.clone()
+                    }
+                })
+                .collect()
+        };
+
+        let root = commit_snapshot(&repo, &[("README.md", "x\n")], &[], "Alice", 1_700_000_000);
+        let base = commit_snapshot(
+            &repo,
+            &[("README.md", "x\n"), ("a.txt", &edit(&[]))],
+            &[root],
+            "Alice",
+            1_700_000_100,
+        );
+        let main = commit_snapshot(
+            &repo,
+            &[("README.md", "x\n"), ("a.txt", &edit(&[2]))],
+            &[base],
+            "Alice",
+            1_700_000_200,
+        );
+        let side = commit_snapshot(
+            &repo,
+            &[("README.md", "x\n"), ("a.txt", &edit(&[18]))],
+            &[base],
+            "Bob",
+            1_700_000_300,
+        );
+        let merge = commit_snapshot(
+            &repo,
+            &[("README.md", "x\n"), ("a.txt", &edit(&[2, 18]))],
+            &[main, side],
+            "Alice",
+            1_700_000_400,
+        );
+        repo.set_head_detached(merge).unwrap();
+
+        // 20 linhas criadas, mais 2 em cada ramo; o merge limpo não acrescenta nada
+        for policy in [
+            MergePolicy::Conflicts,
+            MergePolicy::Skip,
+            MergePolicy::FirstParent,
+        ] {
+            let metrics = analyze_with_merge_policy(temp_dir.path(), policy);
+            assert_eq!(metrics.churn[&PathBuf::from("a.txt")], 24, "{:?}", policy);
+        }
+
+        // Uma resolução própria do merge conta só as linhas que diferem de ambos os pais
+        let resolved = edit(&[2, 18]).replace("editada 2\n", "resolvida 2\n");
+        let merge = commit_snapshot(
+            &repo,
+            &[("README.md", "x\n"), ("a.txt", &resolved)],
+            &[main, side],
+            "Alice",
+            1_700_000_400,
+        );
+        repo.set_head_detached(merge).unwrap();
+        let metrics = analyze_with_merge_policy(temp_dir.path(), MergePolicy::Conflicts);
+        assert_eq!(metrics.churn[&PathBuf::from("a.txt")], 26);
+    }
+
+    #[test]
+    fn test_merge_policy_skip_ignores_merges() {
+        let temp_dir = repo_with_merge();
+        let metrics = analyze_with_merge_policy(temp_dir.path(), MergePolicy::Skip);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("a.txt")).unwrap(), 1);
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_merge_policy_first_parent_counts_merge_once() {
+        let temp_dir = repo_with_merge();
+        let metrics = analyze_with_merge_policy(temp_dir.path(), MergePolicy::FirstParent);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("a.txt")).unwrap(), 2);
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 1);
+        // O autor do ramo lateral não é visitado: o merge é creditado a quem o fez
+        assert_eq!(
+            metrics.authors.get(&PathBuf::from("b.txt")).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_analyze_repository_unifies_authors_by_email() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(&repo, &[("a.txt", Some("1\n"))], "Ana Silva", 1_700_000_000);
+        commit_files(
+            &repo,
+            &[("a.txt", Some("1\n2\n"))],
+            "Ana Silva",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("a.txt", Some("1\n2\n3\n"))],
+            "ana.silva",
+            1_700_000_200,
+        );
+
+        let path = PathBuf::from("a.txt");
+        let by_name =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+        assert_eq!(by_name.authors.get(&path).unwrap().len(), 2);
+
+        let options = AnalysisOptions {
+            author_key: AuthorKey::Email,
+            ..AnalysisOptions::default()
+        };
+        let by_email = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert_eq!(by_email.authors.get(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_git_metrics_apply_commit_credits_co_authors() {
+        let mut metrics = GitMetrics::new();
+        let path = PathBuf::from("src/main.rs");
+        let changes = CommitChanges {
+            files: vec![(path.clone(), 10)],
+            ..CommitChanges::default()
+        };
+
+        metrics.apply_commit(&changes, &["Alice".to_string(), "Bob".to_string()]);
+
+        assert_eq!(*metrics.churn.get(&path).unwrap(), 10);
+        assert_eq!(metrics.authors.get(&path).unwrap().len(), 2);
+    }
+
+    /// `main` altera `a.txt` e `feature` (a partir da mesma base) altera `b.txt` duas vezes.
+    fn repo_with_branches() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let a = numbered_lines(5);
+        let b = numbered_lines(5);
+
+        let base = commit_snapshot(
+            &repo,
+            &[("a.txt", &a), ("b.txt", &b)],
+            &[],
+            "Alice",
+            1_700_000_000,
+        );
+        let main = commit_snapshot(
+            &repo,
+            &[("a.txt", &format!("{}main\n", a)), ("b.txt", &b)],
+            &[base],
+            "Alice",
+            1_700_000_100,
+        );
+        let feature_1 = commit_snapshot(
+            &repo,
+            &[("a.txt", &a), ("b.txt", &format!("{}f1\n", b))],
+            &[base],
+            "Bob",
+            1_700_000_200,
+        );
+        let feature_2 = commit_snapshot(
+            &repo,
+            &[("a.txt", &a), ("b.txt", &format!("{}f1\nf2\n", b))],
+            &[feature_1],
+            "Bob",
+            1_700_000_300,
+        );
+
+        repo.reference("refs/heads/main", main, true, "main")
+            .unwrap();
+        repo.reference("refs/heads/feature", feature_2, true, "feature")
+            .unwrap();
+        repo.reference("refs/tags/v1.0", feature_1, true, "tag")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        temp_dir
+    }
+
+    fn analyze_revisions(repo_path: &Path, revisions: &[&str], all_branches: bool) -> GitMetrics {
+        let options = AnalysisOptions {
+            revisions: revisions.iter().map(|r| r.to_string()).collect(),
+            all_branches,
+            ..AnalysisOptions::default()
+        };
+        analyze_repository(&repo_path.to_path_buf(), &options).unwrap()
+    }
+
+    #[test]
+    fn test_analyze_repository_defaults_to_head() {
+        let temp_dir = repo_with_branches();
+        let metrics = analyze_revisions(temp_dir.path(), &[], false);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("a.txt")).unwrap(), 1);
+        assert!(!metrics.churn.contains_key(&PathBuf::from("b.txt")));
+    }
+
+    #[test]
+    fn test_analyze_repository_range_only_branch_commits() {
+        let temp_dir = repo_with_branches();
+        let metrics = analyze_revisions(temp_dir.path(), &["main..feature"], false);
+
+        assert!(!metrics.churn.contains_key(&PathBuf::from("a.txt")));
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_analyze_repository_tag_range() {
+        let temp_dir = repo_with_branches();
+        let metrics = analyze_revisions(temp_dir.path(), &["v1.0..feature"], false);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_analyze_repository_all_branches() {
+        let temp_dir = repo_with_branches();
+        let metrics = analyze_revisions(temp_dir.path(), &[], true);
+
+        assert_eq!(*metrics.churn.get(&PathBuf::from("a.txt")).unwrap(), 1);
+        assert_eq!(*metrics.churn.get(&PathBuf::from("b.txt")).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_analyze_repository_invalid_revision() {
+        let temp_dir = repo_with_branches();
+        let options = AnalysisOptions {
+            revisions: vec!["nao-existe".to_string()],
+            ..AnalysisOptions::default()
+        };
+
+        assert!(analyze_repository(&temp_dir.path().to_path_buf(), &options).is_err());
+    }
+
+    /// Timestamp de uma data às 12:00 UTC.
+    fn noon(date: &str) -> i64 {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    /// Cria um histórico linear em que o commit `i` adiciona `arquivo_i.txt` na data dada.
+    fn linear_history(dates: &[&str]) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            noon("2023-01-01"),
+        );
+
+        for (i, date) in dates.iter().enumerate() {
+            let path = format!("arquivo_{}.txt", i);
+            commit_files(
+                &repo,
+                &[(path.as_str(), Some("conteudo\n"))],
+                "Alice",
+                noon(date),
+            );
+        }
+
+        temp_dir
+    }
+
+    fn analyze_since(repo_path: &Path, since: &str) -> GitMetrics {
+        let options = AnalysisOptions {
+            since: Some(since.to_string()),
+            ..AnalysisOptions::default()
+        };
+        analyze_repository(&repo_path.to_path_buf(), &options).unwrap()
+    }
+
+    #[test]
+    fn test_since_tolerates_out_of_order_dates() {
+        let temp_dir = linear_history(&["2024-01-05", "2023-12-15", "2024-01-10", "2024-01-20"]);
+        let metrics = analyze_since(temp_dir.path(), "2024-01-02");
+
+        assert!(metrics.churn.contains_key(&PathBuf::from("arquivo_0.txt")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("arquivo_1.txt")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("arquivo_2.txt")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("arquivo_3.txt")));
+    }
+
+    #[test]
+    fn test_since_stops_after_boundary() {
+        let mut dates = vec!["2024-03-01"];
+        dates.extend(["2023-06-01"; SINCE_SLOP]);
+        dates.push("2024-02-01");
+        let temp_dir = linear_history(&dates);
+
+        let metrics = analyze_since(temp_dir.path(), "2024-01-01");
+
+        // O commit com data adiantada fica atrás da fronteira e não é visitado
+        assert!(!metrics.churn.contains_key(&PathBuf::from("arquivo_0.txt")));
+        assert!(metrics
+            .churn
+            .contains_key(&PathBuf::from(format!("arquivo_{}.txt", SINCE_SLOP + 1))));
+        assert_eq!(metrics.churn.len(), 1);
+    }
+
+    #[test]
+    fn test_parallel_analysis_matches_sequential() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", Some(&content))],
+            "Alice",
+            1_700_000_100,
+        );
+        for i in 0..12 {
+            let author = if i % 2 == 0 { "Alice" } else { "Bob" };
+            let body = format!("{}{}\n", content, "x".repeat(i + 1));
+            commit_files(
+                &repo,
+                &[("src/foo.rs", Some(&body)), ("b.txt", Some(&body))],
+                author,
+                1_700_000_200 + i as i64,
+            );
+        }
+        commit_files(
+            &repo,
+            &[("src/foo.rs", None), ("src/core/foo.rs", Some(&content))],
+            "Carla",
+            1_700_001_000,
+        );
+
+        let analyze_with_jobs = |jobs| {
+            let options = AnalysisOptions {
+                jobs,
+                ..AnalysisOptions::default()
+            };
+            analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap()
+        };
+        let sequential = analyze_with_jobs(1);
+        let parallel = analyze_with_jobs(4);
+
+        assert_eq!(sequential.churn, parallel.churn);
+        assert_eq!(sequential.authors, parallel.authors);
+        assert_eq!(sequential.renames, parallel.renames);
+        assert!(parallel
+            .churn
+            .contains_key(&PathBuf::from("src/core/foo.rs")));
+        assert!(!parallel.churn.contains_key(&PathBuf::from("src/foo.rs")));
+    }
+
+    #[test]
+    fn test_cached_analysis_matches_fresh_analysis() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let repo_path = temp_dir.path().to_path_buf();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("src/foo.rs", Some(&content))],
+            "Alice",
+            1_700_000_100,
+        );
+        let first = analyze_repository(&repo_path, &AnalysisOptions::default()).unwrap();
+        assert!(repo.path().join("hotspot-analyzer/cache.json").exists());
+
+        // Commits novos entre os já guardados no cache
+        commit_files(
+            &repo,
+            &[("src/foo.rs", None), ("src/core/foo.rs", Some(&content))],
+            "Bob",
+            1_700_000_200,
+        );
+        commit_files(&repo, &[("b.txt", Some("b\n"))], "Bob", 1_700_000_300);
+
+        let cached = analyze_repository(&repo_path, &AnalysisOptions::default()).unwrap();
+        let options = AnalysisOptions {
+            use_cache: false,
+            ..AnalysisOptions::default()
+        };
+        let fresh = analyze_repository(&repo_path, &options).unwrap();
+
+        assert_eq!(first.churn.get(&PathBuf::from("src/foo.rs")), Some(&20));
+        assert_eq!(cached.churn, fresh.churn);
+        assert_eq!(cached.authors, fresh.authors);
+        assert_eq!(
+            cached.churn.get(&PathBuf::from("src/core/foo.rs")),
+            Some(&20)
+        );
+    }
+
+    #[test]
+    fn test_no_cache_does_not_write_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(&repo, &[("a.txt", Some("a\n"))], "Alice", 1_700_000_000);
+        commit_files(&repo, &[("a.txt", Some("a\nb\n"))], "Alice", 1_700_000_100);
+
+        let options = AnalysisOptions {
+            use_cache: false,
+            ..AnalysisOptions::default()
+        };
+        analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert!(!repo.path().join("hotspot-analyzer").exists());
+    }
+
+    #[test]
+    fn test_path_filter_applies_to_current_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("test/foo.rs", Some(&content)),
+                ("src/contest.rs", Some("c\n")),
+            ],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("test/foo.rs", None), ("src/foo.rs", Some(&content))],
+            "Alice",
+            1_700_000_200,
+        );
+
+        let options = AnalysisOptions {
+            path_filter: PathFilter::new(&[], &["test".to_string()]).unwrap(),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        // O histórico em test/ pertence ao arquivo que hoje está em src/
+        assert_eq!(metrics.churn.get(&PathBuf::from("src/foo.rs")), Some(&20));
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/contest.rs")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("test/foo.rs")));
+    }
+
+    #[test]
+    fn test_gitattributes_excludes_at_analyzed_revision() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_699_999_900,
+        );
+        let before = commit_files(
+            &repo,
+            &[
+                ("src/main.rs", Some("fn main() {}\n")),
+                ("api.pb.go", Some("package api\n")),
+                ("logo.png", Some("png\n")),
+            ],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[(
+                ".gitattributes",
+                Some("*.pb.go linguist-generated\n*.png binary\n"),
+            )],
+            "Alice",
+            1_700_000_100,
+        );
+        repo.reference("refs/tags/v1.0", before, true, "tag")
+            .unwrap();
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/main.rs")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("api.pb.go")));
+        assert!(!metrics.authors.contains_key(&PathBuf::from("api.pb.go")));
+        assert_eq!(
+            metrics.excluded.get(Path::new("api.pb.go")),
+            Some(&ExclusionReason::LinguistGenerated)
+        );
+        // O libgit2 já trata `binary` como sem linhas, então o arquivo nem chega a ter churn
+        assert!(!metrics.churn.contains_key(&PathBuf::from("logo.png")));
+
+        // Na revisão anterior ao .gitattributes, nada é excluído
+        let options = AnalysisOptions {
+            revisions: vec!["v1.0".to_string()],
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("api.pb.go")));
+        assert!(metrics.excluded.is_empty());
+
+        let options = AnalysisOptions {
+            use_gitattributes: false,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("api.pb.go")));
+        assert!(metrics.excluded.is_empty());
+    }
+
+    #[test]
+    fn test_generated_files_excluded_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("src/main.rs", Some("fn main() {}\n")),
+                ("Cargo.lock", Some("[[package]]\n")),
+                ("src/schema.rs", Some("// @generated\nfn schema() {}\n")),
+            ],
+            "Alice",
+            1_700_000_100,
+        );
+        // O cabeçalho vale na revisão analisada: o arquivo deixou de ser gerado
+        commit_files(
+            &repo,
+            &[
+                ("src/api.rs", Some("// DO NOT EDIT\nfn api() {}\n")),
+                ("src/schema.rs", Some("fn schema() {}\n")),
+            ],
+            "Alice",
+            1_700_000_200,
+        );
+        commit_files(
+            &repo,
+            &[("src/api.rs", Some("// DO NOT EDIT\nfn api() { 1 }\n"))],
+            "Alice",
+            1_700_000_300,
+        );
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/main.rs")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/schema.rs")));
+        assert_eq!(
+            metrics.excluded.get(Path::new("Cargo.lock")),
+            Some(&ExclusionReason::Lockfile)
+        );
+        assert_eq!(
+            metrics.excluded.get(Path::new("src/api.rs")),
+            Some(&ExclusionReason::GeneratedHeader)
+        );
+
+        let options = AnalysisOptions {
+            detect_generated: false,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("Cargo.lock")));
+        assert!(metrics.excluded.is_empty());
+    }
+
+    /// Histórico com uma mudança real em `a.rs` seguida de uma reformatação de `a.rs` e `b.rs`.
+    /// Retorna o OID da reformatação.
+    fn repo_with_reformat(temp_dir: &TempDir) -> Oid {
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_files(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {\n  let x = 1;\n}\n")),
+                ("b.rs", Some("fn b() {\n  let y = 2;\n}\n")),
+            ],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("a.rs", Some("fn a() {\n  let x = 2;\n}\n"))],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {\n    let x = 2;\n}\n")),
+                ("b.rs", Some("fn b() {\n    let y = 2;\n}\n")),
+            ],
+            "Bot",
+            1_700_000_200,
+        )
+    }
+
+    #[test]
+    fn test_ignore_whitespace_skips_reformatting() {
+        let temp_dir = TempDir::new().unwrap();
+        repo_with_reformat(&temp_dir);
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+        assert_eq!(metrics.churn.get(&PathBuf::from("a.rs")), Some(&4));
+        assert_eq!(metrics.churn.get(&PathBuf::from("b.rs")), Some(&2));
+
+        for options in [
+            AnalysisOptions {
+                ignore_whitespace: true,
+                ..AnalysisOptions::default()
+            },
+            AnalysisOptions {
+                ignore_whitespace_change: true,
+                ..AnalysisOptions::default()
+            },
+        ] {
+            let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+            assert_eq!(metrics.churn.get(&PathBuf::from("a.rs")), Some(&2));
+            assert!(!metrics.churn.contains_key(&PathBuf::from("b.rs")));
+            assert_eq!(metrics.authors[&PathBuf::from("a.rs")].len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_ignore_revs_file_skips_listed_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let reformat = repo_with_reformat(&temp_dir);
+        fs::write(
+            temp_dir.path().join(".git-blame-ignore-revs"),
+            format!("# cargo fmt\n{}\n", &reformat.to_string()[..12]),
+        )
+        .unwrap();
+
+        let options = AnalysisOptions {
+            ignore_revs_file: Some(PathBuf::from(".git-blame-ignore-revs")),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert_eq!(metrics.churn.get(&PathBuf::from("a.rs")), Some(&2));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("b.rs")));
+        assert!(!metrics.authors[&PathBuf::from("a.rs")].contains("Bot"));
+
+        let options = AnalysisOptions {
+            ignore_revs_file: Some(PathBuf::from("nao-existe")),
+            ..AnalysisOptions::default()
+        };
+        assert!(analyze_repository(&temp_dir.path().to_path_buf(), &options).is_err());
+    }
+
+    #[test]
+    fn test_ignored_commit_still_follows_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(&repo, &[("old.rs", Some(&content))], "Alice", 1_700_000_100);
+        let rename = commit_files(
+            &repo,
+            &[("old.rs", None), ("new.rs", Some(&content))],
+            "Bot",
+            1_700_000_200,
+        );
+        fs::write(temp_dir.path().join("ignore-revs"), format!("{}\n", rename)).unwrap();
+
+        let options = AnalysisOptions {
+            ignore_revs_file: Some(temp_dir.path().join("ignore-revs")),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert_eq!(metrics.churn.get(&PathBuf::from("new.rs")), Some(&20));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("old.rs")));
+        assert!(!metrics.authors[&PathBuf::from("new.rs")].contains("Bot"));
+    }
+
+    /// Um commit normal em `a.txt` (10 linhas) seguido de um commit que altera 4 arquivos
+    /// com 10 linhas cada.
+    fn repo_with_mass_change(temp_dir: &TempDir) {
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(10);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(&repo, &[("a.txt", Some(&content))], "Alice", 1_700_000_100);
+        let sweep: Vec<(&str, Option<&str>)> = ["b.txt", "c.txt", "d.txt", "e.txt"]
+            .iter()
+            .map(|path| (*path, Some(content.as_str())))
+            .collect();
+        commit_files(&repo, &sweep, "Bot", 1_700_000_200);
+    }
+
+    #[test]
+    fn test_mass_change_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        repo_with_mass_change(&temp_dir);
+
+        let options = AnalysisOptions {
+            max_files_per_commit: Some(3),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert_eq!(metrics.churn.get(&PathBuf::from("a.txt")), Some(&10));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("b.txt")));
+        assert_eq!(metrics.mass_changes.len(), 1);
+        assert_eq!(metrics.mass_changes[0].files, 4);
+        assert_eq!(metrics.mass_changes[0].lines, 40);
+        assert_eq!(metrics.mass_changes[0].summary, "commit");
+    }
+
+    #[test]
+    fn test_excluded_paths_do_not_count_toward_mass_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(10);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("src/main.rs", Some(&content)),
+                ("vendor/a.rs", Some(&content)),
+                ("vendor/b.rs", Some(&content)),
+                ("vendor/c.rs", Some(&content)),
+            ],
+            "Alice",
+            1_700_000_100,
+        );
+
+        let options = AnalysisOptions {
+            max_files_per_commit: Some(3),
+            max_lines_per_commit: Some(20),
+            path_filter: PathFilter::new(&[], &["vendor/**".to_string()]).unwrap(),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert!(metrics.mass_changes.is_empty());
+        assert_eq!(metrics.churn.get(&PathBuf::from("src/main.rs")), Some(&10));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("vendor/a.rs")));
+    }
+
+    #[test]
+    fn test_mass_change_dampened() {
+        let temp_dir = TempDir::new().unwrap();
+        repo_with_mass_change(&temp_dir);
+
+        // 40 linhas com limite 20: peso 0,5
+        let options = AnalysisOptions {
+            max_lines_per_commit: Some(20),
+            mass_change_policy: MassChangePolicy::Dampen,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert_eq!(metrics.churn.get(&PathBuf::from("a.txt")), Some(&10));
+        assert_eq!(metrics.churn.get(&PathBuf::from("b.txt")), Some(&5));
+        assert!(metrics.authors[&PathBuf::from("b.txt")].contains("Bot"));
+        assert_eq!(metrics.mass_changes.len(), 1);
+
+        let options = AnalysisOptions {
+            max_lines_per_commit: Some(40),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert_eq!(metrics.churn.get(&PathBuf::from("b.txt")), Some(&10));
+        assert!(metrics.mass_changes.is_empty());
+    }
+
+    #[test]
+    fn test_author_filter_applied_before_accumulation() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[("src/main.rs", Some("fn main() {}\n"))],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("Cargo.toml", Some("[dependencies]\nserde = \"1\"\n"))],
+            "dependabot[bot]",
+            1_700_000_200,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("src/main.rs", Some("fn main() { 1 }\n")),
+                ("Cargo.toml", Some("[dependencies]\nserde = \"2\"\n")),
+            ],
+            "Bob",
+            1_700_000_300,
+        );
+
+        let analyze = |filter: AuthorFilter| {
+            let options = AnalysisOptions {
+                author_filter: filter,
+                ..AnalysisOptions::default()
+            };
+            analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap()
+        };
+
+        let metrics = analyze(AuthorFilter::new(&[], &[], true).unwrap());
+        assert_eq!(metrics.churn.get(&PathBuf::from("Cargo.toml")), Some(&2));
+        assert!(!metrics.authors[&PathBuf::from("Cargo.toml")].contains("dependabot[bot]"));
+
+        let metrics = analyze(AuthorFilter::new(&[], &["^Bob$".to_string()], false).unwrap());
+        assert_eq!(metrics.churn.get(&PathBuf::from("src/main.rs")), Some(&1));
+        assert_eq!(metrics.authors[&PathBuf::from("Cargo.toml")].len(), 1);
+
+        let metrics = analyze(AuthorFilter::new(&["^Alice$".to_string()], &[], false).unwrap());
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/main.rs")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("Cargo.toml")));
+    }
+
+    #[test]
+    fn test_binary_files_tracked_separately() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[
+                ("logo.png", Some("PNG\0\0\0\0\0\0")),
+                ("src/main.rs", Some("fn main() {}\n")),
+            ],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[("logo.png", Some("PNG\0\0\0"))],
+            "Bob",
+            1_700_000_200,
+        );
+        commit_files(
+            &repo,
+            &[("logo.png", None), ("assets/logo.png", Some("PNG\0\0\0"))],
+            "Bob",
+            1_700_000_300,
+        );
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+
+        assert!(metrics.churn.contains_key(&PathBuf::from("src/main.rs")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("logo.png")));
+        assert!(!metrics
+            .churn
+            .contains_key(&PathBuf::from("assets/logo.png")));
+
+        let binary = &metrics.binary_churn[&PathBuf::from("assets/logo.png")];
+        assert_eq!(binary.changes, 2);
+        assert_eq!(binary.size_delta, 6);
+        assert_eq!(binary.bytes_changed, 12);
+    }
+
+    #[test]
+    fn test_binary_files_follow_exclusions_and_path_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[
+                (".gitattributes", Some("vendor/** linguist-vendored\n")),
+                ("vendor/lib.so", Some("ELF\0\0\0")),
+                ("assets/logo.png", Some("PNG\0\0\0")),
+                ("docs/diagrama.png", Some("PNG\0\0\0")),
+            ],
+            "Alice",
+            1_700_000_100,
+        );
+
+        let options = AnalysisOptions {
+            path_filter: PathFilter::new(&[], &["assets".to_string()]).unwrap(),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        let binaries: Vec<&PathBuf> = metrics.binary_churn.keys().collect();
+        assert_eq!(binaries, vec![&PathBuf::from("docs/diagrama.png")]);
+        assert_eq!(
+            metrics.excluded.get(&PathBuf::from("vendor/lib.so")),
+            Some(&ExclusionReason::LinguistVendored)
+        );
+    }
+
+    #[test]
+    fn test_deleted_files_dropped_unless_requested() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        let before_delete = commit_files(
+            &repo,
+            &[("a.rs", Some("a\n")), ("b.rs", Some("b\n"))],
+            "Alice",
+            1_700_000_100,
+        );
+        repo.reference("refs/tags/v1.0", before_delete, true, "tag")
+            .unwrap();
+        commit_files(&repo, &[("b.rs", None)], "Alice", 1_700_000_200);
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("a.rs")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("b.rs")));
+        assert!(!metrics.authors.contains_key(&PathBuf::from("b.rs")));
+        assert!(metrics.deleted.is_empty());
+
+        let options = AnalysisOptions {
+            include_deleted: true,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("b.rs")));
+        assert!(metrics.deleted.contains(&PathBuf::from("b.rs")));
+        assert!(!metrics.deleted.contains(&PathBuf::from("a.rs")));
+
+        // Na revisão anterior à remoção, o arquivo ainda existe
+        let options = AnalysisOptions {
+            revisions: vec!["v1.0".to_string()],
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        assert!(metrics.churn.contains_key(&PathBuf::from("b.rs")));
+    }
+
+    #[test]
+    fn test_function_granularity_maps_hunks_to_functions() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        commit_files(
+            &repo,
+            &[(
+                "lib.rs",
+                Some("fn a() {\n    x();\n}\n\nfn b() {\n    y();\n}\n"),
+            )],
+            "Alice",
+            1_700_000_100,
+        );
+        commit_files(
+            &repo,
+            &[(
+                "lib.rs",
+                Some("use z;\n\nfn a() {\n    x();\n}\n\nfn b() {\n    y();\n    z();\n}\n"),
+            )],
+            "Bob",
+            1_700_000_200,
+        );
+
+        let options = AnalysisOptions {
+            granularity: Granularity::Function,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        let functions = &metrics.functions[&PathBuf::from("lib.rs")];
+
+        // `a` só foi criada; as linhas fora de funções (`use z;`) não contam
+        assert_eq!(functions["a"].churn, 3);
+        assert_eq!(functions["a"].authors.len(), 1);
+        assert_eq!(functions["b"].churn, 4);
+        assert_eq!(functions["b"].authors.len(), 2);
+
+        let metrics =
+            analyze_repository(&temp_dir.path().to_path_buf(), &AnalysisOptions::default())
+                .unwrap();
+        assert!(metrics.functions.is_empty());
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        assert!(sample_indices(10, 0).is_empty());
+        assert_eq!(sample_indices(3, 5), vec![0, 1, 2]);
+        assert_eq!(sample_indices(7, 1), vec![0]);
+        assert_eq!(sample_indices(10, 4), vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn test_trend_samples_follow_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = numbered_lines(20);
+
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            1_700_000_000,
+        );
+        let before_rename =
+            commit_files(&repo, &[("old.rs", Some(&content))], "Alice", 1_700_000_100);
+        commit_files(
+            &repo,
+            &[("old.rs", None), ("new.rs", Some(&content))],
+            "Alice",
+            1_700_000_200,
+        );
+
+        let options = AnalysisOptions {
+            trend_samples: 3,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        let samples = &metrics.trend_samples;
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[1].commit, before_rename);
+        assert_eq!(samples[1].position, 1);
+        assert_eq!(
+            samples[1].historical_paths(Path::new("new.rs")),
+            vec![PathBuf::from("old.rs"), PathBuf::from("new.rs")]
+        );
+        assert_eq!(
+            samples[0].historical_paths(Path::new("new.rs")),
+            vec![PathBuf::from("new.rs")]
+        );
+    }
+
+    #[test]
+    fn test_parse_date_none() {
+        let result = parse_date(None, DateBound::Start).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_parse_date_valid() {
+        let result = parse_date(Some("2024-01-15"), DateBound::Start).unwrap();
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_parse_date_relative() {
+        let result = parse_date(Some("2 weeks ago"), DateBound::Start)
+            .unwrap()
+            .unwrap();
+        assert!(result < Utc::now());
+    }
+
+    #[test]
+    fn test_resolve_date_from_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let oid = commit_files(&repo, &[("a.txt", Some("a\n"))], "Alice", 1_700_000_000);
+        repo.reference("refs/tags/v1.2.0", oid, true, "tag")
+            .unwrap();
+
+        let resolve = |s| resolve_date(&repo, Some(s), DateBound::Start, DateField::Committer);
+        assert_eq!(
+            resolve("v1.2.0").unwrap().unwrap().timestamp(),
+            1_700_000_000
+        );
+        assert!(resolve("nao-existe").is_err());
+    }
+
+    #[test]
+    fn test_since_tag_limits_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_files(&repo, &[("a.txt", Some("a\n"))], "Alice", 1_700_000_000);
+        commit_files(&repo, &[("a.txt", Some("a\nb\n"))], "Alice", 1_700_000_100);
+        let tag = commit_files(&repo, &[("b.txt", Some("b\n"))], "Alice", 1_700_000_200);
+        repo.reference("refs/tags/v1.0", tag, true, "tag").unwrap();
+        commit_files(&repo, &[("c.txt", Some("c\n"))], "Alice", 1_700_000_300);
+
+        let metrics = analyze_since(temp_dir.path(), "v1.0");
+
+        assert!(!metrics.churn.contains_key(&PathBuf::from("a.txt")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("b.txt")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("c.txt")));
+    }
+
+    #[test]
+    fn test_parse_date_until_covers_whole_day() {
+        let start = parse_date(Some("2024-12-31"), DateBound::Start)
+            .unwrap()
+            .unwrap();
+        let end = parse_date(Some("2024-12-31"), DateBound::End)
+            .unwrap()
+            .unwrap();
+        assert_eq!(end - start, chrono::Duration::seconds(24 * 3600 - 1));
+    }
+
+    #[test]
+    fn test_until_includes_commits_of_final_day() {
+        let temp_dir = linear_history(&["2024-12-30", "2024-12-31", "2025-01-02"]);
+        let options = AnalysisOptions {
+            until: Some("2024-12-31".to_string()),
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert!(metrics.churn.contains_key(&PathBuf::from("arquivo_0.txt")));
+        assert!(metrics.churn.contains_key(&PathBuf::from("arquivo_1.txt")));
+        assert!(!metrics.churn.contains_key(&PathBuf::from("arquivo_2.txt")));
+    }
+
+    #[test]
+    fn test_date_field_author() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            noon("2023-01-01"),
+        );
+
+        // Escrito em 2023, mas gravado (rebase) em 2024
+        let tree_oid = {
+            let mut index = repo.index().unwrap();
+            fs::write(temp_dir.path().join("a.txt"), "a\n").unwrap();
+            index.add_path(Path::new("a.txt")).unwrap();
+            index.write().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_oid).unwrap();
+        let author =
+            Signature::new("Alice", "a@example.com", &Time::new(noon("2023-06-01"), 0)).unwrap();
+        let committer =
+            Signature::new("Alice", "a@example.com", &Time::new(noon("2024-06-01"), 0)).unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &author,
+            &committer,
+            "rebase",
+            &tree,
+            &[&parent],
+        )
+        .unwrap();
+
+        let analyze_with_field = |date_field| {
+            let options = AnalysisOptions {
+                since: Some("2024-01-01".to_string()),
+                date_field,
+                ..AnalysisOptions::default()
+            };
+            analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap()
+        };
+
+        assert!(analyze_with_field(DateField::Committer)
+            .churn
+            .contains_key(&PathBuf::from("a.txt")));
+        assert!(analyze_with_field(DateField::Author).churn.is_empty());
+    }
+
+    #[test]
+    fn test_date_field_author_with_rebased_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_files(
+            &repo,
+            &[("README.md", Some("inicio\n"))],
+            "Alice",
+            noon("2023-01-01"),
+        );
+
+        let commit = |file: &str, authored: &str, committed: &str| {
+            let mut index = repo.index().unwrap();
+            fs::write(temp_dir.path().join(file), "a\n").unwrap();
+            index.add_path(Path::new(file)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let author =
+                Signature::new("Alice", "a@example.com", &Time::new(noon(authored), 0)).unwrap();
+            let committer =
+                Signature::new("Alice", "a@example.com", &Time::new(noon(committed), 0)).unwrap();
+            let parent = repo.head().unwrap().peel_to_commit().unwrap();
+            repo.commit(Some("HEAD"), &author, &committer, "c", &tree, &[&parent])
+                .unwrap();
+        };
+
+        // Escrito e gravado dentro do período
+        commit("recente.txt", "2024-03-01", "2024-03-01");
+        // Seguido de vários commits escritos antes do período, mas gravados (rebase) depois
+        for i in 0..SINCE_SLOP + 2 {
+            commit(&format!("antigo{}.txt", i), "2023-06-01", "2024-06-01");
+        }
+
+        let options = AnalysisOptions {
+            since: Some("2024-01-01".to_string()),
+            date_field: DateField::Author,
+            ..AnalysisOptions::default()
+        };
+        let metrics = analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+
+        assert!(metrics.churn.contains_key(&PathBuf::from("recente.txt")));
+        assert_eq!(metrics.churn.len(), 1);
     }
 
     #[test]
     fn test_parse_date_invalid() {
-        let result = parse_date(Some("invalid-date"));
+        let result = parse_date(Some("invalid-date"), DateBound::Start);
         assert!(result.is_err());
     }
 }
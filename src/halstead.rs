@@ -0,0 +1,325 @@
+use crate::syntax::{Language, SourceCode, Token, TokenKind};
+use crate::types::Halstead;
+use std::collections::HashSet;
+
+/// Palavras-chave de cada linguagem, contadas como operadores. Os demais identificadores,
+/// números e strings são operandos.
+fn keywords(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Rust => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+            "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "type", "unsafe",
+            "use", "where", "while",
+        ],
+        Language::Python => &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+            "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+            "with", "yield",
+        ],
+        Language::JavaScript => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "debugger",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "export",
+            "extends",
+            "finally",
+            "for",
+            "function",
+            "if",
+            "implements",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "of",
+            "private",
+            "protected",
+            "public",
+            "readonly",
+            "return",
+            "static",
+            "super",
+            "switch",
+            "throw",
+            "try",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "while",
+            "with",
+            "yield",
+        ],
+        Language::Java => &[
+            "abstract",
+            "assert",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "continue",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "extends",
+            "final",
+            "finally",
+            "for",
+            "if",
+            "implements",
+            "import",
+            "instanceof",
+            "interface",
+            "native",
+            "new",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "super",
+            "switch",
+            "synchronized",
+            "throw",
+            "throws",
+            "transient",
+            "try",
+            "void",
+            "volatile",
+            "while",
+        ],
+        Language::Go => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "fallthrough",
+            "for",
+            "func",
+            "go",
+            "goto",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "type",
+            "var",
+        ],
+        Language::C => &[
+            "auto",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "extern",
+            "for",
+            "goto",
+            "if",
+            "inline",
+            "namespace",
+            "new",
+            "operator",
+            "private",
+            "protected",
+            "public",
+            "register",
+            "return",
+            "sizeof",
+            "static",
+            "struct",
+            "switch",
+            "template",
+            "throw",
+            "try",
+            "typedef",
+            "union",
+            "using",
+            "virtual",
+            "volatile",
+            "while",
+        ],
+    }
+}
+
+/// Métricas de Halstead do código. Operadores são a pontuação e as palavras-chave; parênteses,
+/// colchetes e chaves contam uma vez por par, pelo símbolo de abertura.
+pub fn measure(code: &SourceCode) -> Halstead {
+    measure_tokens(code.language, &code.tokens)
+}
+
+/// Métricas de Halstead de uma parte do código, como o corpo de uma função.
+pub fn measure_tokens<'a: 'b, 'b>(
+    language: Language,
+    tokens: impl IntoIterator<Item = &'b Token<'a>>,
+) -> Halstead {
+    let keywords = keywords(language);
+
+    let mut operators = HashSet::new();
+    let mut operands = HashSet::new();
+    let mut total_operators = 0;
+    let mut total_operands = 0;
+
+    for token in tokens {
+        let is_operator = match token.kind {
+            TokenKind::Punct => {
+                if [")", "]", "}"].contains(&token.text) {
+                    continue;
+                }
+                true
+            }
+            TokenKind::Word => keywords.contains(&token.text),
+            TokenKind::Number | TokenKind::Str => false,
+        };
+
+        if is_operator {
+            operators.insert(token.text);
+            total_operators += 1;
+        } else {
+            operands.insert(token.text);
+            total_operands += 1;
+        }
+    }
+
+    let vocabulary = operators.len() + operands.len();
+    let length = total_operators + total_operands;
+
+    let volume = if vocabulary > 0 {
+        length as f64 * (vocabulary as f64).log2()
+    } else {
+        0.0
+    };
+    let difficulty = if operands.is_empty() {
+        0.0
+    } else {
+        (operators.len() as f64 / 2.0) * (total_operands as f64 / operands.len() as f64)
+    };
+
+    Halstead {
+        distinct_operators: operators.len(),
+        distinct_operands: operands.len(),
+        total_operators,
+        total_operands,
+        volume,
+        difficulty,
+        effort: difficulty * volume,
+    }
+}
+
+/// Índice de manutenibilidade, normalizado entre 0 e 100 como no Visual Studio:
+/// `max(0, (171 - 5,2 ln(V) - 0,23 CC - 16,2 ln(LOC)) * 100 / 171)`, com `V` o volume de
+/// Halstead, `CC` a complexidade ciclomática e `LOC` as linhas de código. Quanto maior, mais
+/// fácil de manter.
+pub fn maintainability_index(halstead: &Halstead, cyclomatic: usize, lines_of_code: usize) -> f64 {
+    if lines_of_code == 0 || halstead.volume <= 0.0 {
+        return 100.0;
+    }
+
+    let raw = 171.0
+        - 5.2 * halstead.volume.ln()
+        - 0.23 * cyclomatic as f64
+        - 16.2 * (lines_of_code as f64).ln();
+
+    (raw * 100.0 / 171.0).clamp(0.0, 100.0)
+}
+
+/// Linhas com pelo menos um token, ou seja, sem linhas em branco nem só de comentário.
+pub fn lines_of_code(code: &SourceCode) -> usize {
+    lines_of_tokens(&code.tokens)
+}
+
+/// Linhas com pelo menos um dos tokens dados.
+pub fn lines_of_tokens<'a: 'b, 'b>(tokens: impl IntoIterator<Item = &'b Token<'a>>) -> usize {
+    let lines: HashSet<usize> = tokens.into_iter().map(|token| token.line).collect();
+    lines.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_counts_operators_and_operands() {
+        // Operadores: fn ( , { let = + ; (8 distintos, 10 no total, com os pares de + e ;)
+        // Operandos: add a b c 1 (5 distintos, 8 no total)
+        let code = SourceCode::parse(
+            Language::Rust,
+            "fn add(a, b) {\n    let c = a + 1;\n    c + b;\n}\n",
+        );
+        let halstead = measure(&code);
+
+        assert_eq!(halstead.distinct_operators, 8);
+        assert_eq!(halstead.total_operators, 10);
+        assert_eq!(halstead.distinct_operands, 5);
+        assert_eq!(halstead.total_operands, 8);
+
+        let volume = 18.0 * 13f64.log2();
+        assert!((halstead.volume - volume).abs() < 1e-9);
+        assert!((halstead.difficulty - 4.0 * 8.0 / 5.0).abs() < 1e-9);
+        assert!((halstead.effort - halstead.difficulty * volume).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure_empty_code() {
+        let code = SourceCode::parse(Language::Python, "# só comentário\n");
+        assert_eq!(measure(&code), Halstead::default());
+    }
+
+    #[test]
+    fn test_maintainability_index() {
+        let halstead = Halstead {
+            volume: 1000.0,
+            ..Halstead::default()
+        };
+        let expected =
+            (171.0 - 5.2 * 1000f64.ln() - 0.23 * 10.0 - 16.2 * 100f64.ln()) * 100.0 / 171.0;
+
+        assert!((maintainability_index(&halstead, 10, 100) - expected).abs() < 1e-9);
+        assert_eq!(maintainability_index(&Halstead::default(), 0, 0), 100.0);
+
+        let huge = Halstead {
+            volume: 1e12,
+            ..Halstead::default()
+        };
+        assert_eq!(maintainability_index(&huge, 500, 100_000), 0.0);
+    }
+
+    #[test]
+    fn test_lines_of_code() {
+        let code = SourceCode::parse(Language::C, "// x\nint a;\n\n/* y */\nint b;\n");
+        assert_eq!(lines_of_code(&code), 2);
+    }
+}
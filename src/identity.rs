@@ -0,0 +1,418 @@
+use crate::filters::AuthorFilter;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use git2::{Commit, Mailmap, Repository, Signature};
+use std::fs;
+use std::path::Path;
+
+/// Qual parte da identidade (já resolvida pelo mailmap) distingue um autor de outro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AuthorKey {
+    /// Nome do autor
+    #[default]
+    Name,
+    /// E-mail do autor, sem diferenciar maiúsculas de minúsculas
+    Email,
+}
+
+/// Uma linha de arquivo no formato `.mailmap`.
+#[derive(Debug, PartialEq, Eq)]
+struct MailmapEntry {
+    real_name: Option<String>,
+    real_email: Option<String>,
+    replace_name: Option<String>,
+    replace_email: String,
+}
+
+/// Unifica as várias grafias de um mesmo autor usando o `.mailmap` do repositório e,
+/// opcionalmente, um arquivo de aliases no mesmo formato.
+pub struct IdentityResolver {
+    mailmap: Mailmap,
+    key: AuthorKey,
+    filter: AuthorFilter,
+}
+
+impl IdentityResolver {
+    pub fn new(repo: &Repository, alias_file: Option<&Path>, key: AuthorKey) -> Result<Self> {
+        let mut mailmap = repo
+            .mailmap()
+            .context("Falha ao ler o .mailmap do repositório")?;
+
+        if let Some(alias_file) = alias_file {
+            let content = fs::read_to_string(alias_file).with_context(|| {
+                format!("Falha ao ler arquivo de aliases {}", alias_file.display())
+            })?;
+
+            for entry in content.lines().filter_map(parse_mailmap_line) {
+                mailmap.add_entry(
+                    entry.real_name.as_deref(),
+                    entry.real_email.as_deref(),
+                    entry.replace_name.as_deref(),
+                    &entry.replace_email,
+                )?;
+            }
+        }
+
+        Ok(Self {
+            mailmap,
+            key,
+            filter: AuthorFilter::default(),
+        })
+    }
+
+    /// Descarta os autores recusados por `filter` em [`Self::commit_authors`] e
+    /// [`Self::co_authors`].
+    pub fn with_filter(mut self, filter: AuthorFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Identificador canônico do autor de uma assinatura.
+    pub fn resolve(&self, signature: &Signature) -> String {
+        let resolved = self.canonical(signature);
+        self.key_of(resolved.name(), resolved.email())
+    }
+
+    /// Autor e coautores de um commit que passam pelo filtro, começando pelo autor. Se o
+    /// autor for recusado, o commit inteiro é descartado e a lista fica vazia.
+    pub fn commit_authors(&self, commit: &Commit) -> Vec<String> {
+        let author = commit.author();
+        let resolved = self.canonical(&author);
+        if !self.accepts(resolved.name(), resolved.email()) {
+            return Vec::new();
+        }
+
+        let mut authors = vec![self.resolve(&author)];
+        for co_author in self.co_authors(commit.message().unwrap_or("")) {
+            if !authors.contains(&co_author) {
+                authors.push(co_author);
+            }
+        }
+        authors
+    }
+
+    /// Identificadores canônicos dos coautores citados em trailers `Co-authored-by:` da
+    /// mensagem de commit.
+    pub fn co_authors(&self, message: &str) -> Vec<String> {
+        let mut co_authors = Vec::new();
+
+        for (name, email) in parse_co_authors(message) {
+            let (key, accepted) = match Signature::now(&name, &email) {
+                Ok(signature) => {
+                    let resolved = self.canonical(&signature);
+                    (
+                        self.key_of(resolved.name(), resolved.email()),
+                        self.accepts(resolved.name(), resolved.email()),
+                    )
+                }
+                Err(_) => (
+                    self.key_of(Some(&name), Some(&email)),
+                    self.accepts(Some(&name), Some(&email)),
+                ),
+            };
+            if accepted && !co_authors.contains(&key) {
+                co_authors.push(key);
+            }
+        }
+
+        co_authors
+    }
+
+    /// Assinatura depois do mailmap.
+    fn canonical(&self, signature: &Signature) -> Signature<'static> {
+        self.mailmap
+            .resolve_signature(signature)
+            .unwrap_or_else(|_| signature.to_owned())
+    }
+
+    fn accepts(&self, name: Option<&str>, email: Option<&str>) -> bool {
+        self.filter
+            .matches(name.unwrap_or("").trim(), email.unwrap_or("").trim())
+    }
+
+    fn key_of(&self, name: Option<&str>, email: Option<&str>) -> String {
+        let name = name.map(str::trim).filter(|n| !n.is_empty());
+        let email = email.map(str::trim).filter(|e| !e.is_empty());
+
+        match (self.key, name, email) {
+            (AuthorKey::Email, _, Some(email)) => email.to_lowercase(),
+            (_, Some(name), _) => name.to_string(),
+            (_, None, Some(email)) => email.to_lowercase(),
+            (_, None, None) => "Unknown".to_string(),
+        }
+    }
+}
+
+/// Extrai (nome, e-mail) de cada trailer `Co-authored-by: Nome <email>` da mensagem.
+fn parse_co_authors(message: &str) -> Vec<(String, String)> {
+    const TRAILER: &str = "co-authored-by:";
+
+    trailer_block(message)
+        .into_iter()
+        .filter(|line| {
+            line.get(..TRAILER.len())
+                .is_some_and(|key| key.eq_ignore_ascii_case(TRAILER))
+        })
+        .filter_map(|line| {
+            let value = line[TRAILER.len()..].trim();
+            let (name, rest) = value.split_once('<')?;
+            let (email, _) = rest.split_once('>')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_string(), email.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Linhas do bloco de trailers da mensagem, como no `git interpret-trailers`: o último
+/// parágrafo, desde que não seja o assunto e que todas as suas linhas sejam trailers
+/// (`Chave: valor`) ou continuações indentadas do trailer anterior.
+fn trailer_block(message: &str) -> Vec<&str> {
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in message.lines().map(str::trim_end) {
+        if line.is_empty() {
+            if paragraphs.last().is_some_and(|p| !p.is_empty()) {
+                paragraphs.push(Vec::new());
+            }
+        } else {
+            paragraphs.last_mut().unwrap().push(line);
+        }
+    }
+    paragraphs.retain(|p| !p.is_empty());
+
+    let is_trailer = |line: &str| {
+        line.split_once(':').is_some_and(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    };
+    let is_continuation = |line: &str| line.starts_with([' ', '\t']);
+
+    match paragraphs.split_last() {
+        Some((last, rest))
+            if !rest.is_empty()
+                && last.first().is_some_and(|line| is_trailer(line))
+                && last
+                    .iter()
+                    .all(|line| is_trailer(line) || is_continuation(line)) =>
+        {
+            last.clone()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Interpreta uma linha do `.mailmap`, nas formas aceitas pelo git:
+///
+/// ```text
+/// Nome Real <email@commit>
+/// <email@real> <email@commit>
+/// Nome Real <email@real> <email@commit>
+/// Nome Real <email@real> Nome Commit <email@commit>
+/// ```
+fn parse_mailmap_line(line: &str) -> Option<MailmapEntry> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let (first_name, rest) = line.split_once('<')?;
+    let (first_email, rest) = rest.split_once('>')?;
+
+    let non_empty = |s: &str| {
+        let s = s.trim();
+        (!s.is_empty()).then(|| s.to_string())
+    };
+
+    match rest.split_once('<') {
+        None => Some(MailmapEntry {
+            real_name: non_empty(first_name),
+            real_email: None,
+            replace_name: None,
+            replace_email: first_email.trim().to_string(),
+        }),
+        Some((second_name, rest)) => {
+            let (second_email, _) = rest.split_once('>')?;
+            Some(MailmapEntry {
+                real_name: non_empty(first_name),
+                real_email: non_empty(first_email),
+                replace_name: non_empty(second_name),
+                replace_email: second_email.trim().to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn signature(name: &str, email: &str) -> Signature<'static> {
+        Signature::now(name, email).unwrap()
+    }
+
+    #[test]
+    fn test_parse_mailmap_line_name_only() {
+        let entry = parse_mailmap_line("Ana Silva <ana@example.com>").unwrap();
+        assert_eq!(entry.real_name.as_deref(), Some("Ana Silva"));
+        assert_eq!(entry.real_email, None);
+        assert_eq!(entry.replace_email, "ana@example.com");
+    }
+
+    #[test]
+    fn test_parse_mailmap_line_full() {
+        let entry =
+            parse_mailmap_line("Ana Silva <ana@example.com> ana.silva <ana@old.com> # antigo")
+                .unwrap();
+        assert_eq!(entry.real_name.as_deref(), Some("Ana Silva"));
+        assert_eq!(entry.real_email.as_deref(), Some("ana@example.com"));
+        assert_eq!(entry.replace_name.as_deref(), Some("ana.silva"));
+        assert_eq!(entry.replace_email, "ana@old.com");
+    }
+
+    #[test]
+    fn test_parse_mailmap_line_comment_and_invalid() {
+        assert!(parse_mailmap_line("# comentário").is_none());
+        assert!(parse_mailmap_line("   ").is_none());
+        assert!(parse_mailmap_line("sem email").is_none());
+    }
+
+    #[test]
+    fn test_parse_co_authors() {
+        let message = "Implementa parser\n\nCorpo do commit.\n\n\
+                       Co-authored-by: Bob Souza <bob@example.com>\n\
+                       co-authored-by: Carla <carla@example.com>\n\
+                       Signed-off-by: Ana <ana@example.com>\n";
+
+        let co_authors = parse_co_authors(message);
+
+        assert_eq!(
+            co_authors,
+            vec![
+                ("Bob Souza".to_string(), "bob@example.com".to_string()),
+                ("Carla".to_string(), "carla@example.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_co_authors_ignores_malformed() {
+        assert!(parse_co_authors("msg\n\nCo-authored-by: sem email").is_empty());
+        assert!(parse_co_authors("msg\n\nCo-authored-by: <so@email.com>").is_empty());
+        assert!(parse_co_authors("sem trailers").is_empty());
+    }
+
+    #[test]
+    fn test_parse_co_authors_only_from_trailer_block() {
+        // Citações no corpo (revert, squash, explicação da convenção) não são trailers
+        let message = "Revert \"Implementa parser\"\n\n\
+                       Desfaz o commit abaixo:\n\n    \
+                       Implementa parser\n\n    \
+                       Co-authored-by: Bob Souza <bob@example.com>\n\n\
+                       Para creditar, use Co-authored-by: Nome <email> no fim.\n\
+                       Co-authored-by: Dani <dani@example.com>\n\n\
+                       Signed-off-by: Ana <ana@example.com>\n\
+                       Co-authored-by: Carla <carla@example.com>\n";
+
+        assert_eq!(
+            parse_co_authors(message),
+            vec![("Carla".to_string(), "carla@example.com".to_string())]
+        );
+
+        // O último parágrafo só é um bloco de trailers se todas as linhas forem trailers
+        let message = "msg\n\nVeja a discussão.\nCo-authored-by: Bob <bob@example.com>\n";
+        assert!(parse_co_authors(message).is_empty());
+
+        // O assunto nunca é um bloco de trailers
+        assert!(parse_co_authors("Co-authored-by: Bob <bob@example.com>").is_empty());
+    }
+
+    #[test]
+    fn test_co_authors_resolved_through_mailmap() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        fs::write(
+            temp_dir.path().join(".mailmap"),
+            "Bob Souza <bob@example.com> <bob@laptop>\n",
+        )
+        .unwrap();
+
+        let resolver = IdentityResolver::new(&repo, None, AuthorKey::Name).unwrap();
+        let message =
+            "msg\n\nCo-authored-by: bob <bob@laptop>\nCo-authored-by: Bob Souza <bob@example.com>";
+
+        assert_eq!(resolver.co_authors(message), vec!["Bob Souza".to_string()]);
+    }
+
+    #[test]
+    fn test_resolver_uses_repository_mailmap() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        fs::write(
+            temp_dir.path().join(".mailmap"),
+            "Ana Silva <ana@example.com> <ana.silva@old.com>\n",
+        )
+        .unwrap();
+
+        let resolver = IdentityResolver::new(&repo, None, AuthorKey::Name).unwrap();
+
+        assert_eq!(
+            resolver.resolve(&signature("ana.silva", "ana.silva@old.com")),
+            "Ana Silva"
+        );
+        assert_eq!(
+            resolver.resolve(&signature("Ana Silva", "ana@example.com")),
+            "Ana Silva"
+        );
+    }
+
+    #[test]
+    fn test_resolver_uses_alias_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let alias_file = temp_dir.path().join("aliases");
+        fs::write(
+            &alias_file,
+            "Ana Silva <ana@example.com> Ana S. <ana@laptop>\n",
+        )
+        .unwrap();
+
+        let resolver = IdentityResolver::new(&repo, Some(&alias_file), AuthorKey::Name).unwrap();
+
+        assert_eq!(
+            resolver.resolve(&signature("Ana S.", "ana@laptop")),
+            "Ana Silva"
+        );
+        assert_eq!(
+            resolver.resolve(&signature("Bob", "bob@example.com")),
+            "Bob"
+        );
+    }
+
+    #[test]
+    fn test_co_authors_filtered() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        let filter = AuthorFilter::new(&[], &[], true).unwrap();
+        let resolver = IdentityResolver::new(&repo, None, AuthorKey::Name)
+            .unwrap()
+            .with_filter(filter);
+        let message = "msg\n\nCo-authored-by: Bob <bob@example.com>\n\
+                       Co-authored-by: dependabot[bot] <support@github.com>";
+
+        assert_eq!(resolver.co_authors(message), vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn test_resolver_keyed_by_email() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        let resolver = IdentityResolver::new(&repo, None, AuthorKey::Email).unwrap();
+
+        assert_eq!(
+            resolver.resolve(&signature("Ana Silva", "Ana@Example.com")),
+            resolver.resolve(&signature("ana.silva", "ana@example.com"))
+        );
+    }
+}
@@ -1,74 +1,125 @@
+mod attributes;
+mod cache;
 mod cli;
+mod cognitive;
 mod complexity;
+mod cyclomatic;
+mod dates;
+mod filters;
+mod generated;
 mod git_analyzer;
+mod halstead;
+mod identity;
 mod output;
 mod score;
+mod syntax;
+#[cfg(test)]
+mod test_support;
+mod trend;
 mod types;
 
 use anyhow::Result;
 use clap::Parser;
 use cli::Cli;
+use git_analyzer::{Granularity, MassChangePolicy};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 fn main() -> Result<()> {
     let args = Cli::parse();
 
-    let git_metrics = git_analyzer::analyze_repository(
-        &args.repo,
-        args.since.as_deref(),
-        args.until.as_deref(),
-    )?;
+    let git_metrics = git_analyzer::analyze_repository(&args.repo, &args.analysis_options()?)?;
 
-    let files: Vec<PathBuf> = git_metrics.churn.keys().cloned().collect();
+    if !git_metrics.excluded.is_empty() {
+        eprintln!("{}", output::exclusion_summary(&git_metrics.excluded));
+    }
+    if !git_metrics.mass_changes.is_empty() {
+        let dampened = args.mass_changes == MassChangePolicy::Dampen;
+        eprintln!(
+            "{}",
+            output::mass_change_report(&git_metrics.mass_changes, dampened)
+        );
+    }
 
-    let files_to_analyze: Vec<PathBuf> = files
-        .iter()
-        .filter(|path| {
-            let path_str = path.to_string_lossy();
+    let files_to_analyze: Vec<PathBuf> = git_metrics.churn.keys().cloned().collect();
 
-            if !args.include.is_empty() {
-                if !args.include.iter().any(|pattern| path_str.contains(pattern)) {
-                    return false;
-                }
-            }
+    let mut all_metrics = if args.granularity == Granularity::Function {
+        let function_complexity = match git_metrics.revision {
+            Some(revision) => complexity::analyze_revision_functions(
+                &args.repo,
+                revision,
+                &files_to_analyze,
+                args.complexity_metric,
+            )?,
+            None => HashMap::new(),
+        };
 
-            if !args.exclude.is_empty() {
-                if args.exclude.iter().any(|pattern| path_str.contains(pattern)) {
-                    return false;
-                }
-            }
+        score::calculate_function_scores(&git_metrics, &function_complexity)
+    } else {
+        let file_complexity = match git_metrics.revision {
+            Some(revision) => complexity::analyze_revision(
+                &args.repo,
+                revision,
+                &files_to_analyze,
+                args.complexity_metric,
+            )?,
+            None => HashMap::new(),
+        };
 
-            true
-        })
-        .map(|p| args.repo.join(p))
-        .collect();
+        let complexity_map: HashMap<PathBuf, f64> = file_complexity
+            .iter()
+            .map(|(path, complexity)| (path.clone(), complexity.value))
+            .collect();
 
-    let complexity_map = complexity::analyze_multiple_files(&files_to_analyze, &args.repo);
+        let fallback: HashSet<PathBuf> = file_complexity
+            .iter()
+            .filter(|(_, complexity)| complexity.fallback)
+            .map(|(path, _)| path.clone())
+            .collect();
 
-    let mut all_metrics = score::calculate_scores(&git_metrics, &complexity_map);
+        let mut all_metrics = score::calculate_scores(&git_metrics, &complexity_map, &fallback);
+        complexity::annotate(&mut all_metrics, &file_complexity);
+        all_metrics
+    };
 
     all_metrics.truncate(args.top);
+    trend::annotate(
+        &args.repo,
+        &git_metrics.trend_samples,
+        &mut all_metrics,
+        args.complexity_metric,
+    )?;
+
+    let mut binaries = score::rank_binaries(&git_metrics);
+    binaries.truncate(args.top);
 
     if args.json {
         if let Some(path) = &args.out {
-            output::save_json(&all_metrics, path)?;
+            output::save_json(&all_metrics, &binaries, path)?;
             println!("JSON salvo em: {}", path.display());
         } else {
-            let json = serde_json::to_string_pretty(&all_metrics)?;
-            println!("{}", json);
+            println!("{}", output::json_report(&all_metrics, &binaries)?);
         }
     } else if args.csv {
         if let Some(path) = &args.out {
             output::save_csv(&all_metrics, path)?;
             println!("CSV salvo em: {}", path.display());
+            if !binaries.is_empty() {
+                let binaries_path = output::binaries_csv_path(path);
+                output::save_binaries_csv(&binaries, &binaries_path)?;
+                println!("CSV dos binários salvo em: {}", binaries_path.display());
+            }
         } else {
             println!("Use --out para especificar o arquivo de saída CSV");
         }
     } else if let Some(path) = &args.out {
-        output::save_markdown(&all_metrics, path)?;
+        output::save_markdown(&all_metrics, &binaries, path)?;
         println!("Markdown salvo em: {}", path.display());
     } else {
         output::print_table(&all_metrics);
+        if !binaries.is_empty() {
+            output::print_binary_table(&binaries);
+        }
     }
 
     Ok(())
@@ -1,8 +1,10 @@
-use crate::types::FileMetrics;
+use crate::types::{BinaryChurn, ExclusionReason, FileMetrics, MassChange};
 use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tabled::{settings::Style, Table, Tabled};
 
 #[derive(Tabled)]
@@ -17,16 +19,87 @@ struct TableRow {
     authors: usize,
     #[tabled(rename = "Score")]
     score: String,
+    #[tabled(rename = "Tendência")]
+    trend: String,
 }
 
+/// Identificador do item do ranking: o caminho do arquivo ou, no ranking por função,
+/// `arquivo::função`.
+fn identifier(m: &FileMetrics) -> String {
+    match &m.function {
+        Some(function) => format!("{}::{}", m.path.display(), function),
+        None => m.path.display().to_string(),
+    }
+}
+
+/// Identificador para exibição, indicando arquivos que não existem mais na revisão analisada.
+fn display_path(m: &FileMetrics) -> String {
+    if m.deleted {
+        format!("{} (removido)", identifier(m))
+    } else {
+        identifier(m)
+    }
+}
+
+/// Inclinação da tendência de complexidade, quando ela foi amostrada em mais de um commit.
+fn trend_slope(m: &FileMetrics) -> Option<f64> {
+    m.trend
+        .as_ref()
+        .filter(|trend| trend.series.len() > 1)
+        .map(|trend| trend.slope)
+}
+
+/// Resumo da tendência de complexidade: a direção e a inclinação por commit.
+fn trend_label(m: &FileMetrics) -> String {
+    let Some(slope) = trend_slope(m) else {
+        return "-".to_string();
+    };
+
+    let direction = if slope > TREND_EPSILON {
+        "↑"
+    } else if slope < -TREND_EPSILON {
+        "↓"
+    } else {
+        "→"
+    };
+    format!("{} {:+.2}", direction, slope)
+}
+
+/// Inclinações menores que isso, em módulo, são mostradas como estáveis.
+const TREND_EPSILON: f64 = 0.005;
+
 impl From<&FileMetrics> for TableRow {
     fn from(m: &FileMetrics) -> Self {
         Self {
-            path: m.path.display().to_string(),
+            path: display_path(m),
             churn: m.churn,
             complexity: format!("{:.2}", m.complexity),
             authors: m.authors,
             score: format!("{:.2}", m.score),
+            trend: trend_label(m),
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct BinaryRow {
+    #[tabled(rename = "Arquivo")]
+    path: String,
+    #[tabled(rename = "Mudanças")]
+    changes: usize,
+    #[tabled(rename = "Variação (bytes)")]
+    size_delta: i64,
+    #[tabled(rename = "Bytes alterados")]
+    bytes_changed: u64,
+}
+
+impl From<&BinaryChurn> for BinaryRow {
+    fn from(b: &BinaryChurn) -> Self {
+        Self {
+            path: b.path.display().to_string(),
+            changes: b.changes,
+            size_delta: b.size_delta,
+            bytes_changed: b.bytes_changed,
         }
     }
 }
@@ -37,25 +110,127 @@ pub fn print_table(metrics: &[FileMetrics]) {
     println!("{}", table);
 }
 
-pub fn save_json(metrics: &[FileMetrics], path: &Path) -> Result<()> {
-    let json = serde_json::to_string_pretty(metrics)?;
+pub fn print_binary_table(binaries: &[BinaryChurn]) {
+    let rows: Vec<BinaryRow> = binaries.iter().map(BinaryRow::from).collect();
+    let table = Table::new(rows).with(Style::rounded()).to_string();
+    println!("\nChurn de arquivos binários:\n{}", table);
+}
+
+/// Resumo dos arquivos excluídos da análise, agrupados por motivo. Ex.:
+/// `Arquivos excluídos: 3 (2 linguist-generated, 1 -diff)`.
+pub fn exclusion_summary(excluded: &BTreeMap<PathBuf, ExclusionReason>) -> String {
+    let mut counts: BTreeMap<ExclusionReason, usize> = BTreeMap::new();
+    for reason in excluded.values() {
+        *counts.entry(*reason).or_insert(0) += 1;
+    }
+
+    let reasons: Vec<String> = counts
+        .iter()
+        .map(|(reason, count)| format!("{} {}", count, reason))
+        .collect();
+
+    format!(
+        "Arquivos excluídos: {} ({})",
+        excluded.len(),
+        reasons.join(", ")
+    )
+}
+
+/// Relatório dos commits de mudança em massa, um por linha depois do cabeçalho.
+pub fn mass_change_report(mass_changes: &[MassChange], dampened: bool) -> String {
+    let action = if dampened {
+        "com churn reduzido"
+    } else {
+        "excluídos"
+    };
+    let mut report = format!(
+        "Commits de mudança em massa {}: {}",
+        action,
+        mass_changes.len()
+    );
+
+    for change in mass_changes {
+        let short_id = change.commit.get(..7).unwrap_or(&change.commit);
+        report.push_str(&format!(
+            "\n  {} {} arquivos, {} linhas  {}",
+            short_id, change.files, change.lines, change.summary
+        ));
+    }
+
+    report
+}
+
+/// Documento JSON: os hotspots e o churn dos arquivos binários.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    hotspots: Vec<JsonHotspot<'a>>,
+    binaries: &'a [BinaryChurn],
+}
+
+/// Hotspot no JSON, com o mesmo identificador das demais saídas.
+#[derive(Serialize)]
+struct JsonHotspot<'a> {
+    id: String,
+    #[serde(flatten)]
+    metrics: &'a FileMetrics,
+}
+
+pub fn json_report(metrics: &[FileMetrics], binaries: &[BinaryChurn]) -> Result<String> {
+    let report = JsonReport {
+        hotspots: metrics
+            .iter()
+            .map(|m| JsonHotspot {
+                id: identifier(m),
+                metrics: m,
+            })
+            .collect(),
+        binaries,
+    };
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+pub fn save_json(metrics: &[FileMetrics], binaries: &[BinaryChurn], path: &Path) -> Result<()> {
+    let json = json_report(metrics, binaries)?;
     let mut file = File::create(path)?;
     file.write_all(json.as_bytes())?;
     Ok(())
 }
 
+/// Valor com duas casas decimais, ou vazio quando a métrica não se aplica ao arquivo.
+fn optional(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.2}", v)).unwrap_or_default()
+}
+
 pub fn save_csv(metrics: &[FileMetrics], path: &Path) -> Result<()> {
     let mut writer = csv::Writer::from_path(path)?;
 
-    writer.write_record(&["Arquivo", "Churn", "Complexidade", "Autores", "Score"])?;
+    writer.write_record([
+        "Arquivo",
+        "Removido",
+        "Churn",
+        "Complexidade",
+        "Autores",
+        "Score",
+        "Volume",
+        "Dificuldade",
+        "Esforço",
+        "Manutenibilidade",
+        "Tendência",
+    ])?;
 
     for m in metrics {
         writer.write_record(&[
-            m.path.display().to_string(),
+            identifier(m),
+            if m.deleted { "sim" } else { "não" }.to_string(),
             m.churn.to_string(),
             format!("{:.2}", m.complexity),
             m.authors.to_string(),
             format!("{:.2}", m.score),
+            optional(m.halstead.map(|h| h.volume)),
+            optional(m.halstead.map(|h| h.difficulty)),
+            optional(m.halstead.map(|h| h.effort)),
+            optional(m.maintainability_index),
+            optional(trend_slope(m)),
         ])?;
     }
 
@@ -63,22 +238,64 @@ pub fn save_csv(metrics: &[FileMetrics], path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn save_markdown(metrics: &[FileMetrics], path: &Path) -> Result<()> {
+/// Arquivo CSV com o churn dos binários, ao lado do CSV principal: `relatorio.csv` vira
+/// `relatorio.binaries.csv`.
+pub fn binaries_csv_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.binaries.csv", stem))
+}
+
+pub fn save_binaries_csv(binaries: &[BinaryChurn], path: &Path) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    writer.write_record(["Arquivo", "Mudanças", "Variação (bytes)", "Bytes alterados"])?;
+
+    for b in binaries {
+        writer.write_record(&[
+            b.path.display().to_string(),
+            b.changes.to_string(),
+            b.size_delta.to_string(),
+            b.bytes_changed.to_string(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn save_markdown(metrics: &[FileMetrics], binaries: &[BinaryChurn], path: &Path) -> Result<()> {
     let mut content = String::from("# Análise de Hotspots\n\n");
-    content.push_str("| Arquivo | Churn | Complexidade | Autores | Score |\n");
-    content.push_str("|---------|-------|--------------|---------|-------|\n");
+    content.push_str("| Arquivo | Churn | Complexidade | Autores | Score | Tendência |\n");
+    content.push_str("|---------|-------|--------------|---------|-------|-----------|\n");
 
     for m in metrics {
         content.push_str(&format!(
-            "| {} | {} | {:.2} | {} | {:.2} |\n",
-            m.path.display(),
+            "| {} | {} | {:.2} | {} | {:.2} | {} |\n",
+            display_path(m),
             m.churn,
             m.complexity,
             m.authors,
-            m.score
+            m.score,
+            trend_label(m)
         ));
     }
 
+    if !binaries.is_empty() {
+        content.push_str("\n## Churn de arquivos binários\n\n");
+        content.push_str("| Arquivo | Mudanças | Variação (bytes) | Bytes alterados |\n");
+        content.push_str("|---------|----------|------------------|-----------------|\n");
+
+        for b in binaries {
+            content.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                b.path.display(),
+                b.changes,
+                b.size_delta,
+                b.bytes_changed
+            ));
+        }
+    }
+
     let mut file = File::create(path)?;
     file.write_all(content.as_bytes())?;
     Ok(())
@@ -87,7 +304,7 @@ pub fn save_markdown(metrics: &[FileMetrics], path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use crate::types::{ComplexityTrend, Halstead, TrendPoint};
     use tempfile::TempDir;
 
     fn create_test_metrics() -> Vec<FileMetrics> {
@@ -98,6 +315,8 @@ mod tests {
                 complexity: 50.5,
                 authors: 3,
                 score: 75.25,
+                deleted: false,
+                ..Default::default()
             },
             FileMetrics {
                 path: PathBuf::from("src/lib.rs"),
@@ -105,6 +324,8 @@ mod tests {
                 complexity: 25.3,
                 authors: 2,
                 score: 40.15,
+                deleted: false,
+                ..Default::default()
             },
         ]
     }
@@ -117,6 +338,8 @@ mod tests {
             complexity: 50.5,
             authors: 2,
             score: 75.0,
+            deleted: false,
+            ..Default::default()
         };
 
         let row = TableRow::from(&metrics);
@@ -125,6 +348,81 @@ mod tests {
         assert_eq!(row.complexity, "50.50");
         assert_eq!(row.authors, 2);
         assert_eq!(row.score, "75.00");
+        assert_eq!(row.trend, "-");
+
+        let deleted = FileMetrics {
+            deleted: true,
+            ..metrics
+        };
+        assert_eq!(TableRow::from(&deleted).path, "test.rs (removido)");
+
+        let function = FileMetrics {
+            function: Some("Parser::parse".to_string()),
+            ..deleted
+        };
+        assert_eq!(
+            TableRow::from(&function).path,
+            "test.rs::Parser::parse (removido)"
+        );
+    }
+
+    #[test]
+    fn test_trend_label() {
+        let point = |complexity: f64| TrendPoint {
+            complexity,
+            ..TrendPoint::default()
+        };
+        let with_slope = |slope: f64| FileMetrics {
+            trend: Some(ComplexityTrend {
+                slope,
+                series: vec![point(10.0), point(12.0)],
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(trend_label(&with_slope(0.5)), "↑ +0.50");
+        assert_eq!(trend_label(&with_slope(-1.25)), "↓ -1.25");
+        assert_eq!(trend_label(&with_slope(0.001)), "→ +0.00");
+
+        // Um único ponto não forma tendência
+        let single = FileMetrics {
+            trend: Some(ComplexityTrend {
+                slope: 0.0,
+                series: vec![point(10.0)],
+            }),
+            ..Default::default()
+        };
+        assert_eq!(trend_label(&single), "-");
+    }
+
+    #[test]
+    fn test_exclusion_summary() {
+        let excluded = BTreeMap::from([
+            (PathBuf::from("a.pb.go"), ExclusionReason::LinguistGenerated),
+            (PathBuf::from("b.pb.go"), ExclusionReason::LinguistGenerated),
+            (PathBuf::from("logo.png"), ExclusionReason::NoDiff),
+        ]);
+
+        assert_eq!(
+            exclusion_summary(&excluded),
+            "Arquivos excluídos: 3 (2 linguist-generated, 1 -diff)"
+        );
+    }
+
+    #[test]
+    fn test_mass_change_report() {
+        let mass_changes = vec![MassChange {
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            summary: "Atualiza cabeçalhos de licença".to_string(),
+            files: 312,
+            lines: 624,
+        }];
+
+        assert_eq!(
+            mass_change_report(&mass_changes, false),
+            "Commits de mudança em massa excluídos: 1\n  \
+             0123456 312 arquivos, 624 linhas  Atualiza cabeçalhos de licença"
+        );
     }
 
     #[test]
@@ -133,7 +431,13 @@ mod tests {
         let json_path = temp_dir.path().join("output.json");
 
         let metrics = create_test_metrics();
-        let result = save_json(&metrics, &json_path);
+        let binaries = vec![BinaryChurn {
+            path: PathBuf::from("assets/logo.png"),
+            changes: 3,
+            size_delta: -512,
+            bytes_changed: 2048,
+        }];
+        let result = save_json(&metrics, &binaries, &json_path);
 
         assert!(result.is_ok());
         assert!(json_path.exists());
@@ -141,6 +445,13 @@ mod tests {
         let content = std::fs::read_to_string(&json_path).unwrap();
         assert!(content.contains("main.rs"));
         assert!(content.contains("lib.rs"));
+
+        let report: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(report["hotspots"].as_array().unwrap().len(), 2);
+        assert_eq!(report["hotspots"][0]["id"], "src/main.rs");
+        assert_eq!(report["hotspots"][0]["path"], "src/main.rs");
+        assert_eq!(report["binaries"][0]["path"], "assets/logo.png");
+        assert_eq!(report["binaries"][0]["size_delta"], -512);
     }
 
     #[test]
@@ -160,13 +471,106 @@ mod tests {
         assert!(content.contains("lib.rs"));
     }
 
+    #[test]
+    fn test_save_csv_marks_deleted_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("output.csv");
+
+        let mut metrics = create_test_metrics();
+        metrics[1].deleted = true;
+        save_csv(&metrics, &csv_path).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].starts_with("Arquivo,Removido,"));
+        assert!(lines[1].starts_with("src/main.rs,não,"));
+        assert!(lines[2].starts_with("src/lib.rs,sim,"));
+    }
+
+    #[test]
+    fn test_save_csv_includes_halstead_and_maintainability() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("output.csv");
+
+        let mut metrics = create_test_metrics();
+        metrics[0].halstead = Some(Halstead {
+            volume: 120.0,
+            difficulty: 7.5,
+            effort: 900.0,
+            ..Default::default()
+        });
+        metrics[0].maintainability_index = Some(61.234);
+        // O segundo arquivo caiu na indentação: sem Halstead nem índice
+        metrics[1].max_indentation = Some(3);
+        save_csv(&metrics, &csv_path).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with(",Score,Volume,Dificuldade,Esforço,Manutenibilidade,Tendência"));
+        assert!(lines[1].ends_with(",75.25,120.00,7.50,900.00,61.23,"));
+        assert!(lines[2].ends_with(",40.15,,,,,"));
+    }
+
+    #[test]
+    fn test_save_csv_includes_trend_slope() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("output.csv");
+
+        let mut metrics = create_test_metrics();
+        metrics[0].trend = Some(ComplexityTrend {
+            slope: -1.5,
+            series: vec![TrendPoint::default(), TrendPoint::default()],
+        });
+        // Sem tendência (`--trend-samples 0`) a coluna fica vazia
+        save_csv(&metrics, &csv_path).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with(",Tendência"));
+        assert!(lines[1].ends_with(",-1.50"));
+        assert!(lines[2].ends_with(","));
+    }
+
+    #[test]
+    fn test_json_report_identifies_functions() {
+        let metrics = vec![FileMetrics {
+            path: PathBuf::from("src/parser.rs"),
+            function: Some("Parser::parse".to_string()),
+            ..Default::default()
+        }];
+
+        let report: serde_json::Value =
+            serde_json::from_str(&json_report(&metrics, &[]).unwrap()).unwrap();
+        assert_eq!(report["hotspots"][0]["id"], "src/parser.rs::Parser::parse");
+        assert_eq!(report["hotspots"][0]["function"], "Parser::parse");
+    }
+
+    #[test]
+    fn test_save_binaries_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = binaries_csv_path(&temp_dir.path().join("output.csv"));
+        assert_eq!(csv_path, temp_dir.path().join("output.binaries.csv"));
+
+        let binaries = vec![BinaryChurn {
+            path: PathBuf::from("assets/logo.png"),
+            changes: 3,
+            size_delta: -512,
+            bytes_changed: 2048,
+        }];
+        save_binaries_csv(&binaries, &csv_path).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with("Arquivo,Mudanças"));
+        assert!(content.contains("assets/logo.png,3,-512,2048"));
+    }
+
     #[test]
     fn test_save_markdown() {
         let temp_dir = TempDir::new().unwrap();
         let md_path = temp_dir.path().join("output.md");
 
         let metrics = create_test_metrics();
-        let result = save_markdown(&metrics, &md_path);
+        let result = save_markdown(&metrics, &[], &md_path);
 
         assert!(result.is_ok());
         assert!(md_path.exists());
@@ -177,4 +581,22 @@ mod tests {
         assert!(content.contains("lib.rs"));
         assert!(content.contains("|")); // Markdown table syntax
     }
+
+    #[test]
+    fn test_save_markdown_with_binaries() {
+        let temp_dir = TempDir::new().unwrap();
+        let md_path = temp_dir.path().join("output.md");
+
+        let binaries = vec![BinaryChurn {
+            path: PathBuf::from("assets/logo.png"),
+            changes: 3,
+            size_delta: -512,
+            bytes_changed: 2048,
+        }];
+        save_markdown(&create_test_metrics(), &binaries, &md_path).unwrap();
+
+        let content = std::fs::read_to_string(&md_path).unwrap();
+        assert!(content.contains("## Churn de arquivos binários"));
+        assert!(content.contains("| assets/logo.png | 3 | -512 | 2048 |"));
+    }
 }
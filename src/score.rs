@@ -1,19 +1,38 @@
-use crate::git_analyzer::GitMetrics;
-use crate::types::FileMetrics;
-use std::collections::HashMap;
+use crate::git_analyzer::{FunctionHistory, GitMetrics};
+use crate::types::{BinaryChurn, FileMetrics};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+/// Scores por arquivo. A complexidade é normalizada pelo maior valor da mesma escala: os
+/// arquivos em `fallback`, cuja complexidade veio da indentação em vez da métrica pedida, são
+/// comparados só entre si, já que somas de indentação e contagens de decisões não estão na
+/// mesma escala.
 pub fn calculate_scores(
     git_metrics: &GitMetrics,
     complexity_map: &HashMap<PathBuf, f64>,
+    fallback: &HashSet<PathBuf>,
 ) -> Vec<FileMetrics> {
     let mut file_metrics = Vec::new();
 
     let max_churn = git_metrics.churn.values().max().copied().unwrap_or(1) as f64;
-    let max_complexity = complexity_map.values().copied().fold(0.0f64, f64::max).max(1.0);
+    let max_of = |is_fallback: bool| {
+        complexity_map
+            .iter()
+            .filter(|(path, _)| fallback.contains(*path) == is_fallback)
+            .map(|(_, complexity)| *complexity)
+            .fold(0.0f64, f64::max)
+            .max(1.0)
+    };
+    let max_complexity = max_of(false);
+    let max_fallback_complexity = max_of(true);
 
     for (path, &churn) in &git_metrics.churn {
         let complexity = *complexity_map.get(path).unwrap_or(&0.0);
+        let max_complexity = if fallback.contains(path) {
+            max_fallback_complexity
+        } else {
+            max_complexity
+        };
         let authors = git_metrics
             .authors
             .get(path)
@@ -32,6 +51,12 @@ pub fn calculate_scores(
             complexity,
             authors,
             score,
+            deleted: git_metrics.deleted.contains(path),
+            function: None,
+            max_indentation: None,
+            halstead: None,
+            maintainability_index: None,
+            trend: None,
         });
     }
 
@@ -40,17 +65,91 @@ pub fn calculate_scores(
     file_metrics
 }
 
+/// Scores por função, com a mesma fórmula de [`calculate_scores`] aplicada ao churn e aos
+/// autores de cada função. Só entram as funções que existem na revisão analisada, ou seja,
+/// que aparecem em `function_complexity`.
+pub fn calculate_function_scores(
+    git_metrics: &GitMetrics,
+    function_complexity: &HashMap<PathBuf, HashMap<String, f64>>,
+) -> Vec<FileMetrics> {
+    let functions: Vec<(&PathBuf, &String, &FunctionHistory, f64)> = git_metrics
+        .functions
+        .iter()
+        .flat_map(|(path, functions)| {
+            functions.iter().filter_map(move |(name, history)| {
+                let complexity = *function_complexity.get(path)?.get(name)?;
+                Some((path, name, history, complexity))
+            })
+        })
+        .collect();
+
+    let max_churn = functions
+        .iter()
+        .map(|(_, _, history, _)| history.churn)
+        .max()
+        .unwrap_or(1)
+        .max(1) as f64;
+    let max_complexity = functions
+        .iter()
+        .map(|(_, _, _, complexity)| *complexity)
+        .fold(0.0f64, f64::max)
+        .max(1.0);
+
+    let mut function_metrics: Vec<FileMetrics> = functions
+        .into_iter()
+        .map(|(path, name, history, complexity)| {
+            let authors = history.authors.len().max(1);
+
+            let churn_norm = history.churn as f64 / max_churn;
+            let complex_norm = complexity / max_complexity;
+            let authorship_penalty = 1.0 / (1.0 + (authors as f64).ln_1p());
+
+            FileMetrics {
+                path: path.clone(),
+                function: Some(name.clone()),
+                churn: history.churn,
+                complexity,
+                authors,
+                score: 100.0 * churn_norm * complex_norm * authorship_penalty,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    function_metrics.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap()
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.function.cmp(&b.function))
+    });
+
+    function_metrics
+}
+
+/// Arquivos binários ordenados pelo número de mudanças e, em seguida, pelo volume de bytes.
+pub fn rank_binaries(git_metrics: &GitMetrics) -> Vec<BinaryChurn> {
+    let mut binaries: Vec<BinaryChurn> = git_metrics.binary_churn.values().cloned().collect();
+
+    binaries.sort_by(|a, b| {
+        b.changes
+            .cmp(&a.changes)
+            .then(b.bytes_changed.cmp(&a.bytes_changed))
+            .then(a.path.cmp(&b.path))
+    });
+
+    binaries
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::collections::HashSet;
+    use std::path::Path;
 
     #[test]
     fn test_calculate_scores_basic() {
-        let mut git_metrics = GitMetrics {
-            churn: HashMap::new(),
-            authors: HashMap::new(),
-        };
+        let mut git_metrics = GitMetrics::new();
 
         let path = PathBuf::from("test.rs");
         git_metrics.churn.insert(path.clone(), 100);
@@ -62,7 +161,7 @@ mod tests {
         let mut complexity_map = HashMap::new();
         complexity_map.insert(path.clone(), 50.0);
 
-        let results = calculate_scores(&git_metrics, &complexity_map);
+        let results = calculate_scores(&git_metrics, &complexity_map, &HashSet::new());
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].churn, 100);
@@ -73,10 +172,7 @@ mod tests {
 
     #[test]
     fn test_calculate_scores_sorted_by_score() {
-        let mut git_metrics = GitMetrics {
-            churn: HashMap::new(),
-            authors: HashMap::new(),
-        };
+        let mut git_metrics = GitMetrics::new();
 
         let path1 = PathBuf::from("low_score.rs");
         let path2 = PathBuf::from("high_score.rs");
@@ -96,7 +192,7 @@ mod tests {
         complexity_map.insert(path1.clone(), 10.0);
         complexity_map.insert(path2.clone(), 100.0);
 
-        let results = calculate_scores(&git_metrics, &complexity_map);
+        let results = calculate_scores(&git_metrics, &complexity_map, &HashSet::new());
 
         // Deve estar ordenado por score decrescente
         assert!(results[0].score > results[1].score);
@@ -105,10 +201,7 @@ mod tests {
 
     #[test]
     fn test_calculate_scores_multiple_authors_lower_score() {
-        let mut git_metrics = GitMetrics {
-            churn: HashMap::new(),
-            authors: HashMap::new(),
-        };
+        let mut git_metrics = GitMetrics::new();
 
         let path1 = PathBuf::from("single_author.rs");
         let path2 = PathBuf::from("multiple_authors.rs");
@@ -132,7 +225,7 @@ mod tests {
         complexity_map.insert(path1.clone(), 50.0);
         complexity_map.insert(path2.clone(), 50.0);
 
-        let results = calculate_scores(&git_metrics, &complexity_map);
+        let results = calculate_scores(&git_metrics, &complexity_map, &HashSet::new());
 
         // Arquivo com um único autor deve ter score maior (mais penalidade)
         let score1 = results.iter().find(|m| m.path == path1).unwrap().score;
@@ -143,10 +236,7 @@ mod tests {
 
     #[test]
     fn test_calculate_scores_zero_complexity() {
-        let mut git_metrics = GitMetrics {
-            churn: HashMap::new(),
-            authors: HashMap::new(),
-        };
+        let mut git_metrics = GitMetrics::new();
 
         let path = PathBuf::from("test.rs");
         git_metrics.churn.insert(path.clone(), 100);
@@ -157,10 +247,103 @@ mod tests {
 
         let complexity_map = HashMap::new(); // Sem complexidade
 
-        let results = calculate_scores(&git_metrics, &complexity_map);
+        let results = calculate_scores(&git_metrics, &complexity_map, &HashSet::new());
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].complexity, 0.0);
         assert_eq!(results[0].score, 0.0); // Score deve ser 0 se complexidade é 0
     }
+
+    #[test]
+    fn test_calculate_function_scores() {
+        let mut git_metrics = GitMetrics::new();
+        let path = PathBuf::from("lib.rs");
+        let alice = vec!["Alice".to_string()];
+        git_metrics.add_function_change(&path, "parse", 40, &alice);
+        git_metrics.add_function_change(&path, "main", 10, &alice);
+        git_metrics.add_function_change(&path, "removida", 100, &alice);
+
+        let function_complexity = HashMap::from([(
+            path.clone(),
+            HashMap::from([("parse".to_string(), 8.0), ("main".to_string(), 1.0)]),
+        )]);
+
+        let results = calculate_function_scores(&git_metrics, &function_complexity);
+
+        // `removida` não existe mais na revisão analisada
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, path);
+        assert_eq!(results[0].function.as_deref(), Some("parse"));
+        assert_eq!(results[0].churn, 40);
+        assert_eq!(results[0].authors, 1);
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_calculate_scores_normalizes_fallback_separately() {
+        let mut git_metrics = GitMetrics::new();
+        let code = PathBuf::from("src/score.rs");
+        let config = PathBuf::from(".github/workflows/ci.yml");
+        git_metrics.churn.insert(code.clone(), 100);
+        git_metrics.churn.insert(config.clone(), 100);
+
+        // A soma de indentação do YAML é bem maior que a ciclomática do código
+        let complexity_map = HashMap::from([(code.clone(), 13.0), (config.clone(), 80.0)]);
+        let fallback = HashSet::from([config.clone()]);
+
+        let results = calculate_scores(&git_metrics, &complexity_map, &fallback);
+        let score = |path: &PathBuf| results.iter().find(|m| &m.path == path).unwrap().score;
+
+        // Cada um é o mais complexo da sua escala
+        assert_eq!(score(&code), score(&config));
+        assert_eq!(results.iter().find(|m| m.path == config).unwrap().complexity, 80.0);
+
+        let mut git_metrics = git_metrics;
+        git_metrics.churn.insert(PathBuf::from("deploy.sh"), 100);
+        let complexity_map = HashMap::from([
+            (code.clone(), 13.0),
+            (config.clone(), 80.0),
+            (PathBuf::from("deploy.sh"), 160.0),
+        ]);
+        let fallback = HashSet::from([config.clone(), PathBuf::from("deploy.sh")]);
+        let results = calculate_scores(&git_metrics, &complexity_map, &fallback);
+        let score = |path: &PathBuf| results.iter().find(|m| &m.path == path).unwrap().score;
+        assert!(score(&code) > score(&config));
+    }
+
+    #[test]
+    fn test_rank_binaries() {
+        let mut git_metrics = GitMetrics::new();
+        git_metrics.add_binary_change(&PathBuf::from("logo.png"), 1000);
+        git_metrics.add_binary_change(&PathBuf::from("video.mp4"), 5000);
+        git_metrics.add_binary_change(&PathBuf::from("logo.png"), -200);
+
+        let ranked = rank_binaries(&git_metrics);
+
+        assert_eq!(ranked[0].path, PathBuf::from("logo.png"));
+        assert_eq!(ranked[0].changes, 2);
+        assert_eq!(ranked[0].size_delta, 800);
+        assert_eq!(ranked[0].bytes_changed, 1200);
+        assert_eq!(ranked[1].path, PathBuf::from("video.mp4"));
+    }
+
+    #[test]
+    fn test_calculate_scores_marks_deleted_files() {
+        let mut git_metrics = GitMetrics::new();
+        git_metrics.churn.insert(PathBuf::from("live.rs"), 10);
+        git_metrics.churn.insert(PathBuf::from("old.rs"), 10);
+        git_metrics.deleted.insert(PathBuf::from("old.rs"));
+
+        let results = calculate_scores(&git_metrics, &HashMap::new(), &HashSet::new());
+
+        let deleted = |name: &str| {
+            results
+                .iter()
+                .find(|m| m.path == Path::new(name))
+                .unwrap()
+                .deleted
+        };
+        assert!(!deleted("live.rs"));
+        assert!(deleted("old.rs"));
+    }
 }
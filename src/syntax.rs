@@ -0,0 +1,980 @@
+use std::ops::Range;
+use std::path::Path;
+
+/// Linguagens com análise sintática própria. A análise é léxica e aproximada: reconhece
+/// comentários, strings e os limites das funções, o suficiente para as métricas de
+/// complexidade, sem montar uma árvore sintática.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    /// JavaScript e TypeScript
+    JavaScript,
+    Java,
+    Go,
+    /// C e C++
+    C,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        let language = match extension.as_str() {
+            "rs" => Language::Rust,
+            "py" | "pyi" | "pyw" => Language::Python,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Language::JavaScript,
+            "java" => Language::Java,
+            "go" => Language::Go,
+            "c" | "h" | "cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" | "ino" => Language::C,
+            _ => return None,
+        };
+
+        Some(language)
+    }
+
+    /// Se a linguagem admite código fora de funções (scripts, módulos).
+    pub fn has_top_level_code(self) -> bool {
+        matches!(self, Language::Python | Language::JavaScript)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Identificador ou palavra-chave
+    Word,
+    Number,
+    /// Literal de string ou caractere, com as aspas
+    Str,
+    /// Operador ou pontuação
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Linha onde o token começa (a partir de 1).
+    pub line: usize,
+}
+
+impl Token<'_> {
+    /// Se o token é a palavra-chave ou o operador `text` (e não uma string com esse texto).
+    pub fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::Str && self.text == text
+    }
+}
+
+/// Operadores de mais de um caractere, dos mais longos para os mais curtos.
+const OPERATORS: &[&str] = &[
+    ">>>=", "<<=", ">>=", ">>>", "...", "===", "!==", "**=", "&&=", "||=", "??=", "//=", "->",
+    "=>", "::", "==", "!=", "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=",
+    "%=", "&=", "|=", "^=", "<<", ">>", "**", ":=", "..", "//",
+];
+
+/// Divide o código em tokens, descartando espaços e comentários (e, em C, as diretivas do
+/// pré-processador).
+pub fn tokenize(language: Language, source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let line_comment = if language == Language::Python {
+        "#"
+    } else {
+        "//"
+    };
+
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    let mut at_line_start = true;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        if c == b'\n' {
+            line += 1;
+            pos += 1;
+            at_line_start = true;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let rest = &source[pos..];
+        let line_start = std::mem::replace(&mut at_line_start, false);
+        let start = pos;
+
+        let kind = if rest.starts_with(line_comment)
+            || (language == Language::C && line_start && c == b'#')
+        {
+            pos = line_end(source, pos, language == Language::C);
+            None
+        } else if language != Language::Python && rest.starts_with("/*") {
+            pos = rest[2..].find("*/").map_or(source.len(), |i| pos + i + 4);
+            None
+        } else if let Some(end) = string_end(language, source, pos) {
+            pos = end;
+            Some(TokenKind::Str)
+        } else if c.is_ascii_digit() {
+            pos = number_end(bytes, pos);
+            Some(TokenKind::Number)
+        } else if is_word_byte(c) {
+            pos += bytes[pos..]
+                .iter()
+                .take_while(|&&b| is_word_byte(b))
+                .count();
+            Some(TokenKind::Word)
+        } else {
+            let operator = OPERATORS.iter().find(|op| rest.starts_with(*op));
+            pos += operator.map_or(1, |op| op.len());
+            Some(TokenKind::Punct)
+        };
+
+        if let Some(kind) = kind {
+            tokens.push(Token {
+                kind,
+                text: &source[start..pos],
+                line,
+            });
+        }
+        line += source[start..pos].matches('\n').count();
+    }
+
+    tokens
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// Fim da linha que começa em `pos` (sem a quebra). Com `continuation`, linhas terminadas
+/// em `\` continuam na seguinte, como nas diretivas do C.
+fn line_end(source: &str, pos: usize, continuation: bool) -> usize {
+    let mut end = pos;
+    loop {
+        end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+        if !(continuation && source[..end].trim_end_matches('\r').ends_with('\\')) {
+            return end;
+        }
+        if end == source.len() {
+            return end;
+        }
+        end += 1;
+    }
+}
+
+fn number_end(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        let decimal_point = b == b'.' && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit);
+        if !(b.is_ascii_alphanumeric() || b == b'_' || decimal_point) {
+            break;
+        }
+        pos += 1;
+    }
+    pos
+}
+
+/// Se um literal de string ou caractere começa em `pos`, retorna onde ele termina.
+fn string_end(language: Language, source: &str, pos: usize) -> Option<usize> {
+    let rest = &source[pos..];
+
+    // Prefixos: r"", f"", b"" em Python; b"", r#""# em Rust
+    let prefix_len = match language {
+        Language::Python => {
+            let len = rest.bytes().take_while(|b| b"rRbBfFuU".contains(b)).count();
+            if len > 2 {
+                return None;
+            }
+            len
+        }
+        Language::Rust => {
+            let after_b = rest.strip_prefix('b').unwrap_or(rest);
+            if let Some(raw) = after_b.strip_prefix('r') {
+                let hashes = raw.bytes().take_while(|&b| b == b'#').count();
+                if raw[hashes..].starts_with('"') {
+                    let body = pos + (rest.len() - raw.len()) + hashes + 1;
+                    let closing = format!("\"{}", "#".repeat(hashes));
+                    return Some(
+                        source[body..]
+                            .find(&closing)
+                            .map_or(source.len(), |i| body + i + closing.len()),
+                    );
+                }
+            }
+            rest.len() - after_b.len()
+        }
+        _ => 0,
+    };
+
+    let quote_pos = pos + prefix_len;
+    let quote = *source.as_bytes().get(quote_pos)?;
+
+    match quote {
+        b'"' | b'\'' if source[quote_pos..].starts_with("\"\"\"") => {
+            Some(quoted_end(source, quote_pos, "\"\"\""))
+        }
+        b'\'' if language == Language::Python && source[quote_pos..].starts_with("'''") => {
+            Some(quoted_end(source, quote_pos, "'''"))
+        }
+        b'"' => Some(quoted_end(source, quote_pos, "\"")),
+        b'\'' if language == Language::Rust => rust_char_end(source, quote_pos),
+        b'\'' => Some(quoted_end(source, quote_pos, "'")),
+        b'`' if prefix_len == 0 && matches!(language, Language::JavaScript | Language::Go) => {
+            Some(quoted_end(source, quote_pos, "`"))
+        }
+        _ => None,
+    }
+}
+
+/// Fim de um literal delimitado por `quote` que começa em `start`. Aspas simples ou duplas
+/// terminam na quebra de linha se não forem fechadas antes.
+fn quoted_end(source: &str, start: usize, quote: &str) -> usize {
+    let bytes = source.as_bytes();
+    let single_line = quote == "\"" || quote == "'";
+    let mut pos = start + quote.len();
+
+    while pos < bytes.len() {
+        if bytes[pos] == b'\\' {
+            pos += 2;
+        } else if bytes[pos..].starts_with(quote.as_bytes()) {
+            return (pos + quote.len()).min(bytes.len());
+        } else if single_line && bytes[pos] == b'\n' {
+            return pos;
+        } else {
+            pos += 1;
+        }
+    }
+
+    source.len()
+}
+
+/// Em Rust, `'` abre um caractere (`'a'`, `'\n'`) ou um lifetime (`'a`), que não é literal.
+fn rust_char_end(source: &str, start: usize) -> Option<usize> {
+    let rest = &source[start + 1..];
+    if rest.starts_with('\\') {
+        return Some(quoted_end(source, start, "'"));
+    }
+
+    let c = rest.chars().next()?;
+    rest[c.len_utf8()..]
+        .starts_with('\'')
+        .then(|| start + 1 + c.len_utf8() + 1)
+}
+
+/// Uma função, método ou função anônima com corpo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    /// Primeira e última linhas, inclusivas.
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Tokens da assinatura e do corpo.
+    pub tokens: Range<usize>,
+}
+
+/// Função mais interna de `functions` (em ordem de início) que contém a linha `line`.
+pub fn function_at_line(functions: &[Function], line: usize) -> Option<&Function> {
+    functions
+        .iter()
+        .rev()
+        .find(|function| function.start_line <= line && line <= function.end_line)
+}
+
+/// Nome dado a funções anônimas (closures, lambdas, funções literais).
+pub const ANONYMOUS: &str = "<anônima>";
+
+/// Código já dividido em tokens e funções.
+pub struct SourceCode<'a> {
+    pub language: Language,
+    pub tokens: Vec<Token<'a>>,
+    /// Em ordem de início; funções aninhadas aparecem depois da função que as contém.
+    pub functions: Vec<Function>,
+    /// Para cada token, a função mais interna que o contém.
+    pub owners: Vec<Option<usize>>,
+    /// Indentação de cada linha, em colunas (a linha `n` fica na posição `n - 1`).
+    pub line_indents: Vec<usize>,
+}
+
+impl<'a> SourceCode<'a> {
+    pub fn parse(language: Language, source: &'a str) -> Self {
+        let tokens = tokenize(language, source);
+        let line_indents: Vec<usize> = source.lines().map(indentation).collect();
+        let mut functions = match language {
+            Language::Python => python_functions(&line_indents, &tokens),
+            _ => brace_functions(language, &tokens),
+        };
+        functions.sort_by_key(|function| function.tokens.start);
+
+        let mut owners = vec![None; tokens.len()];
+        for (index, function) in functions.iter().enumerate() {
+            for owner in &mut owners[function.tokens.clone()] {
+                *owner = Some(index);
+            }
+        }
+
+        Self {
+            language,
+            tokens,
+            functions,
+            owners,
+            line_indents,
+        }
+    }
+
+    /// Indentação, em colunas, da linha onde o token `i` começa.
+    pub fn indentation_at(&self, i: usize) -> usize {
+        self.line_indents
+            .get(self.tokens[i].line - 1)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Índices dos tokens que pertencem diretamente a `function`, sem os das funções
+    /// aninhadas nela.
+    pub fn own_tokens(&self, function: usize) -> impl Iterator<Item = usize> + '_ {
+        self.functions[function]
+            .tokens
+            .clone()
+            .filter(move |&i| self.owners[i] == Some(function))
+    }
+
+    /// Índices dos tokens fora de qualquer função.
+    pub fn top_level_tokens(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.tokens.len()).filter(|&i| self.owners[i].is_none())
+    }
+}
+
+/// Bloco entre chaves aberto durante a busca por funções.
+enum OpenBlock {
+    Function(usize),
+    /// Corpo de um tipo (`impl`, classe, interface...), que qualifica os métodos dentro dele.
+    Type(String),
+    Other,
+}
+
+/// Qualifica `name` com os tipos e funções que o contêm: `Tipo::metodo::auxiliar`.
+fn qualified_name<'b>(types: impl Iterator<Item = &'b str>, name: String) -> String {
+    let mut parts: Vec<&str> = types.collect();
+    if parts.is_empty() {
+        return name;
+    }
+    parts.push(&name);
+    parts.join("::")
+}
+
+/// Funções em linguagens com blocos entre chaves: cada `{` cujo cabeçalho (os tokens desde o
+/// último `;`, `{` ou `}`) tem cara de assinatura de função abre uma função.
+fn brace_functions(language: Language, tokens: &[Token]) -> Vec<Function> {
+    let mut functions: Vec<Function> = Vec::new();
+    let mut open_blocks: Vec<OpenBlock> = Vec::new();
+    let mut header_start = 0;
+    let mut paren_depth = 0usize;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Punct {
+            continue;
+        }
+
+        match token.text {
+            "(" => paren_depth += 1,
+            ")" => paren_depth = paren_depth.saturating_sub(1),
+            "{" => {
+                let header = &tokens[header_start..i];
+                let block = if let Some(name) = function_name(language, header) {
+                    // O nome da função mais interna já traz tudo o que a envolve
+                    let mut scopes: Vec<&str> = Vec::new();
+                    for block in &open_blocks {
+                        match block {
+                            OpenBlock::Function(function) => {
+                                scopes = vec![functions[*function].name.as_str()]
+                            }
+                            OpenBlock::Type(name) => scopes.push(name),
+                            OpenBlock::Other => {}
+                        }
+                    }
+                    let name = qualified_name(scopes.into_iter(), name);
+                    functions.push(Function {
+                        name,
+                        start_line: header.first().unwrap_or(token).line,
+                        end_line: token.line,
+                        tokens: header_start..tokens.len(),
+                    });
+                    OpenBlock::Function(functions.len() - 1)
+                } else if let Some(name) = type_name(language, header) {
+                    OpenBlock::Type(name)
+                } else {
+                    OpenBlock::Other
+                };
+                open_blocks.push(block);
+                header_start = i + 1;
+            }
+            "}" => {
+                if let Some(OpenBlock::Function(function)) = open_blocks.pop() {
+                    functions[function].end_line = token.line;
+                    functions[function].tokens.end = i + 1;
+                }
+                header_start = i + 1;
+            }
+            ";" if paren_depth == 0 => header_start = i + 1,
+            _ => {}
+        }
+    }
+
+    // Funções não fechadas (código truncado) vão até o fim
+    if let Some(last) = tokens.last() {
+        for block in open_blocks {
+            if let OpenBlock::Function(function) = block {
+                functions[function].end_line = last.line;
+            }
+        }
+    }
+
+    functions
+}
+
+/// Nome do tipo cujo corpo começa depois de `header`: `impl Tipo`, `impl Trait for Tipo` e
+/// `trait Tipo` em Rust; `class`, `interface`, `enum`, `struct` e `record` nas demais.
+fn type_name(language: Language, header: &[Token]) -> Option<String> {
+    let word_after = |pos: usize| {
+        header
+            .get(pos + 1)
+            .filter(|t| t.kind == TokenKind::Word && !["extends", "implements"].contains(&t.text))
+            .map(|t| t.text.to_string())
+    };
+
+    match language {
+        Language::Rust => {
+            let impl_pos = header.iter().position(|t| t.is("impl"));
+            if let (Some(_), Some(for_pos)) = (impl_pos, header.iter().rposition(|t| t.is("for"))) {
+                return word_after(for_pos);
+            }
+            if let Some(impl_pos) = impl_pos {
+                return word_after(skip_generics(header, impl_pos + 1) - 1);
+            }
+            let trait_pos = header.iter().position(|t| t.is("trait"))?;
+            word_after(trait_pos)
+        }
+        Language::Java | Language::JavaScript | Language::C => {
+            let pos = header.iter().position(|t| {
+                t.kind == TokenKind::Word
+                    && ["class", "interface", "enum", "struct", "record"].contains(&t.text)
+            })?;
+            word_after(pos)
+        }
+        Language::Go | Language::Python => None,
+    }
+}
+
+/// Se há parâmetros genéricos (`<T: Clone>`) começando em `pos`, retorna a posição logo
+/// depois deles; senão, o próprio `pos`.
+fn skip_generics(tokens: &[Token], mut pos: usize) -> usize {
+    if !tokens.get(pos).is_some_and(|t| t.is("<")) {
+        return pos;
+    }
+
+    let mut depth = 0usize;
+    while let Some(token) = tokens.get(pos) {
+        match token.text {
+            "<" => depth += 1,
+            ">" => depth = depth.saturating_sub(1),
+            ">>" => depth = depth.saturating_sub(2),
+            _ => {}
+        }
+        pos += 1;
+        if depth == 0 {
+            break;
+        }
+    }
+    pos
+}
+
+/// Nome da função cujo corpo começa depois de `header`, ou `None` se o bloco não é uma função.
+fn function_name(language: Language, header: &[Token]) -> Option<String> {
+    match language {
+        Language::Rust => {
+            let fn_pos = header.iter().rposition(|t| t.is("fn"))?;
+            Some(word_or_anonymous(header.get(fn_pos + 1)))
+        }
+        Language::Go => go_function_name(header),
+        Language::JavaScript => js_function_name(header),
+        Language::Java | Language::C => c_function_name(language, header),
+        Language::Python => None,
+    }
+}
+
+fn word_or_anonymous(token: Option<&Token>) -> String {
+    match token {
+        Some(token) if token.kind == TokenKind::Word => token.text.to_string(),
+        _ => ANONYMOUS.to_string(),
+    }
+}
+
+/// Índice do `)` que fecha o `(` em `open`.
+fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is("(") {
+            depth += 1;
+        } else if token.is(")") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// `func nome(`, `func (r *T) nome(` ou uma função literal `func(`.
+fn go_function_name(header: &[Token]) -> Option<String> {
+    let func_pos = header.iter().rposition(|t| t.is("func"))?;
+    let next = func_pos + 1;
+
+    if header.get(next).is_some_and(|t| t.is("(")) {
+        // Receptor de método, ou os parâmetros de uma função literal
+        let close = matching_paren(header, next)?;
+        let is_method = header
+            .get(close + 1)
+            .is_some_and(|t| t.kind == TokenKind::Word)
+            && header.get(close + 2).is_some_and(|t| t.is("("));
+        if !is_method {
+            return Some(ANONYMOUS.to_string());
+        }
+
+        // Métodos são qualificados pelo tipo do receptor: `(s *Server)` vira `Server::`
+        let receiver = header[next..close]
+            .iter()
+            .rev()
+            .find(|t| t.kind == TokenKind::Word);
+        let name = header[close + 1].text.to_string();
+        return Some(match receiver {
+            Some(receiver) => format!("{}::{}", receiver.text, name),
+            None => name,
+        });
+    }
+
+    Some(word_or_anonymous(header.get(next)))
+}
+
+fn js_function_name(header: &[Token]) -> Option<String> {
+    // Nome dado por atribuição ou propriedade: `const f = ...`, `f: ...`
+    let assigned_name = || {
+        let pos = header
+            .iter()
+            .position(|t| t.is("=") || t.is(":"))
+            .filter(|&pos| pos > 0)?;
+        let token = &header[pos - 1];
+        (token.kind == TokenKind::Word).then(|| token.text.to_string())
+    };
+
+    if let Some(pos) = header.iter().rposition(|t| t.is("function")) {
+        let mut next = pos + 1;
+        if header.get(next).is_some_and(|t| t.is("*")) {
+            next += 1;
+        }
+        return Some(match header.get(next) {
+            Some(token) if token.kind == TokenKind::Word => token.text.to_string(),
+            _ => assigned_name().unwrap_or_else(|| ANONYMOUS.to_string()),
+        });
+    }
+
+    if header.last().is_some_and(|t| t.is("=>")) {
+        let is_call_argument = header.iter().any(|t| t.is("("))
+            && !header.first().is_some_and(|t| t.is("("))
+            && assigned_name().is_none();
+        return Some(if is_call_argument {
+            ANONYMOUS.to_string()
+        } else {
+            assigned_name().unwrap_or_else(|| ANONYMOUS.to_string())
+        });
+    }
+
+    // Método em classe ou objeto: `nome(params) {`
+    c_function_name(Language::JavaScript, header)
+}
+
+/// Palavras que, no cabeçalho de um bloco, indicam que ele não é o corpo de uma função.
+const NOT_FUNCTION_WORDS: &[&str] = &[
+    "if",
+    "for",
+    "while",
+    "switch",
+    "catch",
+    "synchronized",
+    "new",
+    "return",
+    "else",
+    "do",
+    "try",
+    "class",
+    "struct",
+    "union",
+    "enum",
+    "interface",
+    "namespace",
+    "record",
+    "with",
+];
+
+/// Assinaturas no estilo C: `tipo nome(params) [qualificadores] {`. Em C++, a lista de
+/// inicialização de um construtor (`: a(x), b(y)`) é ignorada.
+fn c_function_name(language: Language, header: &[Token]) -> Option<String> {
+    let mut depth = 0usize;
+    let mut end = header.len();
+    let mut last_group = None;
+
+    for (i, token) in header.iter().enumerate() {
+        if token.is("(") {
+            if depth == 0 {
+                last_group = Some(i);
+            }
+            depth += 1;
+        } else if token.is(")") {
+            depth = depth.checked_sub(1)?;
+        } else if depth == 0 {
+            if token.is("=") || token.is("->") || token.is("=>") || token.is("[") {
+                return None;
+            }
+            if token.kind == TokenKind::Word && NOT_FUNCTION_WORDS.contains(&token.text) {
+                return None;
+            }
+            if language == Language::C && token.is(":") && last_group.is_some() {
+                end = i;
+                break;
+            }
+        }
+    }
+
+    let open = last_group?;
+    matching_paren(&header[..end], open)?;
+
+    let name_pos = open.checked_sub(1)?;
+    let name = &header[name_pos];
+    if name.kind != TokenKind::Word {
+        return None;
+    }
+
+    // Nome qualificado em C++: `Classe::metodo`
+    let mut qualified = name.text.to_string();
+    let mut pos = name_pos;
+    while pos >= 2 && header[pos - 1].is("::") && header[pos - 2].kind == TokenKind::Word {
+        qualified = format!("{}::{}", header[pos - 2].text, qualified);
+        pos -= 2;
+    }
+
+    Some(qualified)
+}
+
+/// Funções em Python: um `def` vai até a primeira linha lógica com indentação menor ou igual
+/// à dele.
+fn python_functions(indents: &[usize], tokens: &[Token]) -> Vec<Function> {
+    let indent_of = |line: usize| indents.get(line - 1).copied().unwrap_or(0);
+
+    let mut functions: Vec<Function> = Vec::new();
+    // Funções abertas e a indentação do `def`
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    // Classes abertas e a indentação do `class`
+    let mut classes: Vec<(String, usize)> = Vec::new();
+    let mut paren_depth = 0usize;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let starts_line = i == 0 || tokens[i - 1].line != token.line;
+
+        if starts_line && paren_depth == 0 {
+            let indent = indent_of(token.line);
+            while let Some(&(function, def_indent)) = open.last() {
+                if indent > def_indent {
+                    break;
+                }
+                functions[function].tokens.end = i;
+                functions[function].end_line = tokens[i - 1].line;
+                open.pop();
+            }
+            while classes
+                .last()
+                .is_some_and(|&(_, class_indent)| indent <= class_indent)
+            {
+                classes.pop();
+            }
+
+            if token.is("class") {
+                if let Some(name) = tokens.get(i + 1).filter(|t| t.kind == TokenKind::Word) {
+                    classes.push((name.text.to_string(), indent));
+                }
+            }
+
+            let def_pos = if token.is("async") { i + 1 } else { i };
+            if tokens.get(def_pos).is_some_and(|t| t.is("def")) {
+                // A função mais interna já traz no nome o que a envolve; faltam as classes
+                // abertas dentro dela
+                let enclosing = open
+                    .last()
+                    .map(|&(function, def_indent)| (functions[function].name.as_str(), def_indent));
+                let scopes = enclosing.iter().map(|&(name, _)| name).chain(
+                    classes
+                        .iter()
+                        .filter(|(_, class_indent)| {
+                            enclosing.is_none_or(|(_, def_indent)| *class_indent > def_indent)
+                        })
+                        .map(|(name, _)| name.as_str()),
+                );
+                let name = qualified_name(scopes, word_or_anonymous(tokens.get(def_pos + 1)));
+                functions.push(Function {
+                    name,
+                    start_line: token.line,
+                    end_line: token.line,
+                    tokens: i..tokens.len(),
+                });
+                open.push((functions.len() - 1, indent));
+            }
+        }
+
+        if token.is("(") || token.is("[") || token.is("{") {
+            paren_depth += 1;
+        } else if token.is(")") || token.is("]") || token.is("}") {
+            paren_depth = paren_depth.saturating_sub(1);
+        }
+    }
+
+    if let Some(last) = tokens.last() {
+        for (function, _) in open {
+            functions[function].end_line = last.line;
+        }
+    }
+
+    functions
+}
+
+/// Indentação de uma linha em colunas, com tabulação até o próximo múltiplo de 8.
+pub fn indentation(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 8 - width % 8,
+            _ => break,
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+        tokens.iter().map(|t| t.text).collect()
+    }
+
+    fn function_names(language: Language, source: &str) -> Vec<String> {
+        SourceCode::parse(language, source)
+            .functions
+            .into_iter()
+            .map(|f| f.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("src/main.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("app.tsx")),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(Language::from_path(Path::new("lib.HPP")), Some(Language::C));
+        assert_eq!(Language::from_path(Path::new("README.md")), None);
+    }
+
+    #[test]
+    fn test_tokenize_skips_comments_and_keeps_strings() {
+        let source = "// if comentário\nlet s = \"if // não é comentário\"; /* for\n while */ x";
+        let tokens = tokenize(Language::Rust, source);
+
+        assert_eq!(
+            texts(&tokens),
+            vec!["let", "s", "=", "\"if // não é comentário\"", ";", "x"]
+        );
+        assert_eq!(tokens[0].line, 2);
+        assert_eq!(tokens[5].line, 3);
+        assert_eq!(tokens[3].kind, TokenKind::Str);
+    }
+
+    #[test]
+    fn test_tokenize_rust_lifetimes_and_raw_strings() {
+        let tokens = tokenize(
+            Language::Rust,
+            "fn f<'a>(x: &'a str) -> char { r#\"a \"if\" b\"#; 'x' }",
+        );
+        let strings: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Str)
+            .map(|t| t.text)
+            .collect();
+
+        assert_eq!(strings, vec!["r#\"a \"if\" b\"#", "'x'"]);
+        assert!(tokens.iter().any(|t| t.is("a")));
+    }
+
+    #[test]
+    fn test_tokenize_python_strings_and_comments() {
+        let source = "x = f'{a}' # if\ny = \"\"\"if\nfor\"\"\"\nz = a // 2";
+        let tokens = tokenize(Language::Python, source);
+
+        assert_eq!(
+            texts(&tokens),
+            vec![
+                "x",
+                "=",
+                "f'{a}'",
+                "y",
+                "=",
+                "\"\"\"if\nfor\"\"\"",
+                "z",
+                "=",
+                "a",
+                "//",
+                "2"
+            ]
+        );
+        assert_eq!(tokens[6].line, 4);
+    }
+
+    #[test]
+    fn test_tokenize_c_skips_preprocessor() {
+        let source = "#if DEBUG\n#define X(a) \\\n  if (a) {}\n#endif\nint x;";
+        assert_eq!(texts(&tokenize(Language::C, source)), vec!["int", "x", ";"]);
+    }
+
+    #[test]
+    fn test_nested_python_functions_qualified_by_enclosing_function() {
+        let source = "\
+class A:
+    def m(self):
+        def helper():
+            pass
+
+    def n(self):
+        def helper():
+            pass
+";
+        assert_eq!(
+            function_names(Language::Python, source),
+            vec!["A::m", "A::m::helper", "A::n", "A::n::helper"]
+        );
+    }
+
+    #[test]
+    fn test_rust_functions() {
+        let source = "impl A {\n    fn a(&self) {\n        let f = |x| { x };\n    }\n}\n\
+                      #[test]\nfn b() -> Result<()> {\n    fn inner() {}\n}\n";
+        let code = SourceCode::parse(Language::Rust, source);
+
+        let names: Vec<&str> = code.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["A::a", "b", "b::inner"]);
+        assert_eq!(
+            (code.functions[0].start_line, code.functions[0].end_line),
+            (2, 4)
+        );
+        assert_eq!(
+            (code.functions[1].start_line, code.functions[1].end_line),
+            (6, 9)
+        );
+    }
+
+    #[test]
+    fn test_go_functions() {
+        let source =
+            "func main() {\n  go func() {}()\n}\nfunc (s *Server) Start(port int) error {\n}\n";
+        assert_eq!(
+            function_names(Language::Go, source),
+            vec!["main", "main::<anônima>", "Server::Start"]
+        );
+    }
+
+    #[test]
+    fn test_javascript_functions() {
+        let source = "function a() {}\n\
+                      const b = async (x) => {\n  items.forEach((i) => { use(i); });\n};\n\
+                      class C extends D {\n  constructor(x) { super(x); }\n  get e() { return 1; }\n}\n\
+                      if (x) { y(); }\n\
+                      const f = function () {};\n";
+        assert_eq!(
+            function_names(Language::JavaScript, source),
+            vec!["a", "b", "b::<anônima>", "C::constructor", "C::e", "f"]
+        );
+    }
+
+    #[test]
+    fn test_java_functions() {
+        let source = "public class A {\n\
+                      @SuppressWarnings(\"x\")\n  public void run() throws IOException {\n\
+                      for (int i = 0; i < n; i++) { if (a) { b(); } }\n\
+                      list.forEach(x -> { f(x); });\n\
+                      Runnable r = new Runnable() { public void run() {} };\n  }\n}\n";
+        assert_eq!(
+            function_names(Language::Java, source),
+            vec!["A::run", "A::run::run"]
+        );
+    }
+
+    #[test]
+    fn test_c_functions() {
+        let source = "struct S { int a; };\n\
+                      static int add(int a, int b) {\n  return a + b;\n}\n\
+                      Foo::Foo(int x) : a_(x), b_(x) {\n}\n\
+                      int table[] = { 1, 2 };\n\
+                      void Foo::bar() const {\n  auto l = [](int y) { return y; };\n}\n";
+        assert_eq!(
+            function_names(Language::C, source),
+            vec!["add", "Foo::Foo", "Foo::bar"]
+        );
+    }
+
+    #[test]
+    fn test_python_functions() {
+        let source = "import os\n\n\
+                      def a(x,\n      y):\n    if x:\n        return y\n\n    return x\n\n\
+                      class B:\n    async def c(self):\n        def d():\n            pass\n        return d\n\n\
+                      print(a(1, 2))\n";
+        let code = SourceCode::parse(Language::Python, source);
+
+        let summary: Vec<(&str, usize, usize)> = code
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.start_line, f.end_line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("a", 3, 8), ("B::c", 11, 14), ("B::c::d", 12, 13)]
+        );
+    }
+
+    #[test]
+    fn test_methods_qualified_by_type() {
+        let rust = "impl<T: Clone> Stack<T> {\n    fn push(&mut self) {}\n}\n\
+                    impl fmt::Display for Stack<u8> {\n    fn fmt(&self) {}\n}\n\
+                    trait Shape {\n    fn area(&self) -> f64 { 0.0 }\n}\n";
+        assert_eq!(
+            function_names(Language::Rust, rust),
+            vec!["Stack::push", "Stack::fmt", "Shape::area"]
+        );
+
+        let cpp =
+            "namespace app {\nclass Parser : public Base {\n  int parse() { return 0; }\n};\n}\n";
+        assert_eq!(function_names(Language::C, cpp), vec!["Parser::parse"]);
+    }
+
+    #[test]
+    fn test_own_tokens_exclude_nested_functions() {
+        let code = SourceCode::parse(Language::Rust, "fn a() { x; fn b() { y; } z; }");
+
+        let own: Vec<&str> = code.own_tokens(0).map(|i| code.tokens[i].text).collect();
+        assert!(own.contains(&"x"));
+        assert!(own.contains(&"z"));
+        assert!(!own.contains(&"y"));
+    }
+}
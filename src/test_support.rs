@@ -0,0 +1,28 @@
+use git2::{Oid, Repository, Signature, Time};
+
+/// Cria um commit com exatamente os arquivos dados (na raiz) e os pais informados, sem
+/// mover `HEAD`.
+pub fn commit_snapshot(
+    repo: &Repository,
+    files: &[(&str, &str)],
+    parents: &[Oid],
+    author: &str,
+    time: i64,
+) -> Oid {
+    let mut builder = repo.treebuilder(None).unwrap();
+    for (path, content) in files {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        builder.insert(path, blob, 0o100644).unwrap();
+    }
+
+    let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+    let signature = Signature::new(author, "dev@example.com", &Time::new(time, 0)).unwrap();
+    let parents: Vec<git2::Commit> = parents
+        .iter()
+        .map(|oid| repo.find_commit(*oid).unwrap())
+        .collect();
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+    repo.commit(None, &signature, &signature, "commit", &tree, &parents)
+        .unwrap()
+}
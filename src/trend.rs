@@ -0,0 +1,181 @@
+use crate::complexity::{self, ComplexityMetric};
+use crate::git_analyzer::TrendSample;
+use crate::types::{ComplexityTrend, FileMetrics, TrendPoint};
+use anyhow::{Context, Result};
+use git2::{ObjectType, Repository, Tree};
+use std::path::Path;
+
+/// Preenche a tendência de complexidade de cada hotspot de `metrics`, lendo dos blobs dos
+/// commits amostrados o arquivo (ou a função, no ranking por função) com o caminho que ele
+/// tinha em cada um.
+pub fn annotate(
+    repo_path: &Path,
+    samples: &[TrendSample],
+    metrics: &mut [FileMetrics],
+    metric: ComplexityMetric,
+) -> Result<()> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+
+    let repo = Repository::open(repo_path).context("Falha ao abrir repositório")?;
+
+    // As amostras chegam do commit mais novo para o mais antigo
+    let mut trees = Vec::with_capacity(samples.len());
+    for sample in samples.iter().rev() {
+        trees.push((sample, repo.find_commit(sample.commit)?.tree()?));
+    }
+
+    for hotspot in metrics.iter_mut() {
+        let mut series = Vec::new();
+        let mut positions = Vec::new();
+
+        for (sample, tree) in &trees {
+            let Some(complexity) = complexity_at(&repo, tree, sample, hotspot, metric)? else {
+                continue;
+            };
+            series.push(TrendPoint {
+                commit: sample.commit.to_string(),
+                date: sample.date.to_rfc3339(),
+                complexity,
+            });
+            positions.push(sample.position as f64);
+        }
+
+        let values: Vec<f64> = series.iter().map(|point| point.complexity).collect();
+        hotspot.trend = Some(ComplexityTrend {
+            slope: slope(&positions, &values),
+            series,
+        });
+    }
+
+    Ok(())
+}
+
+/// Complexidade do hotspot no commit da amostra, ou `None` se ele não existia ali.
+fn complexity_at(
+    repo: &Repository,
+    tree: &Tree,
+    sample: &TrendSample,
+    hotspot: &FileMetrics,
+    metric: ComplexityMetric,
+) -> Result<Option<f64>> {
+    let entry = sample
+        .historical_paths(&hotspot.path)
+        .into_iter()
+        .find_map(|path| tree.get_path(&path).ok().map(|entry| (path, entry)));
+    let Some((path, entry)) = entry else {
+        return Ok(None);
+    };
+    if entry.kind() != Some(ObjectType::Blob) {
+        return Ok(None);
+    }
+
+    let blob = repo.find_blob(entry.id())?;
+    let complexity = match &hotspot.function {
+        Some(function) => {
+            complexity::analyze_content_function_complexity(&path, blob.content(), metric)
+                .get(function)
+                .copied()
+        }
+        None => Some(complexity::analyze_content_complexity(&path, blob.content(), metric).value),
+    };
+
+    Ok(complexity)
+}
+
+/// Inclinação da reta de mínimos quadrados pelos pontos `(x, y)`; 0 com menos de dois
+/// pontos distintos em `x`.
+fn slope(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    if x.len() < 2 {
+        return 0.0;
+    }
+
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let covariance: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = x.iter().map(|x| (x - mean_x).powi(2)).sum();
+
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_analyzer::{self, AnalysisOptions};
+    use crate::test_support::commit_snapshot;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_slope() {
+        assert_eq!(slope(&[], &[]), 0.0);
+        assert_eq!(slope(&[3.0], &[10.0]), 0.0);
+        assert!((slope(&[0.0, 1.0, 2.0], &[1.0, 3.0, 5.0]) - 2.0).abs() < 1e-9);
+        assert!((slope(&[0.0, 2.0, 4.0], &[6.0, 4.0, 2.0]) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trend_follows_file_across_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        let versions: [&[(&str, &str)]; 5] = [
+            &[("README.md", "inicio\n")],
+            &[("README.md", "inicio\n"), ("old.rs", "fn a() {}\n")],
+            &[
+                ("README.md", "inicio\n"),
+                ("old.rs", "fn a(x: bool) {\n    if x {}\n}\n"),
+            ],
+            &[
+                ("README.md", "inicio\n"),
+                ("new.rs", "fn a(x: bool) {\n    if x {}\n}\n"),
+            ],
+            &[
+                ("README.md", "inicio\n"),
+                ("new.rs", "fn a(x: bool, y: bool) {\n    if x && y {}\n}\n"),
+            ],
+        ];
+        let mut parents = Vec::new();
+        for (i, files) in versions.iter().enumerate() {
+            let time = 1_700_000_000 + 100 * i as i64;
+            parents = vec![commit_snapshot(&repo, files, &parents, "Alice", time)];
+        }
+        repo.set_head_detached(parents[0]).unwrap();
+
+        let options = AnalysisOptions {
+            trend_samples: 10,
+            ..AnalysisOptions::default()
+        };
+        let git_metrics =
+            git_analyzer::analyze_repository(&temp_dir.path().to_path_buf(), &options).unwrap();
+        let mut metrics = vec![FileMetrics {
+            path: PathBuf::from("new.rs"),
+            ..FileMetrics::default()
+        }];
+
+        annotate(
+            temp_dir.path(),
+            &git_metrics.trend_samples,
+            &mut metrics,
+            ComplexityMetric::Cyclomatic,
+        )
+        .unwrap();
+
+        let trend = metrics[0].trend.as_ref().unwrap();
+        let series: Vec<f64> = trend.series.iter().map(|p| p.complexity).collect();
+        // O primeiro commit, sem o arquivo, fica de fora
+        assert_eq!(series, vec![1.0, 2.0, 2.0, 3.0]);
+        assert!(trend.slope > 0.0);
+        assert!(trend.series[0].date.starts_with("2023-11-14T22:15:00"));
+    }
+}
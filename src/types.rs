@@ -1,12 +1,122 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileMetrics {
     pub path: PathBuf,
+    /// Função do arquivo, quando o ranking é por função (`--granularity function`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
     pub churn: usize,
     pub complexity: f64,
     pub authors: usize,
     pub score: f64,
+    /// O arquivo não existe mais na revisão analisada.
+    #[serde(default)]
+    pub deleted: bool,
+    /// Maior indentação lógica do arquivo, quando a complexidade veio da indentação.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_indentation: Option<usize>,
+    /// Métricas de Halstead, para linguagens com análise sintática.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halstead: Option<Halstead>,
+    /// Índice de manutenibilidade (0–100, maior é melhor), para linguagens com análise sintática.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability_index: Option<f64>,
+    /// Complexidade ao longo do histórico, quando a tendência foi amostrada.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trend: Option<ComplexityTrend>,
+}
+
+/// Evolução da complexidade de um hotspot nos commits amostrados.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ComplexityTrend {
+    /// Inclinação da reta de mínimos quadrados, em complexidade por commit: positiva quando
+    /// o hotspot está piorando, negativa quando está sendo pago.
+    pub slope: f64,
+    /// Do commit mais antigo para o mais novo; commits em que o arquivo (ou a função) não
+    /// existia ficam de fora.
+    pub series: Vec<TrendPoint>,
 }
 
+/// Complexidade de um hotspot em um commit amostrado.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub commit: String,
+    /// Data do commit (RFC 3339), a mesma usada por `--since`/`--until`.
+    pub date: String,
+    pub complexity: f64,
+}
+
+/// Métricas de Halstead de um arquivo, calculadas a partir dos tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Halstead {
+    /// Operadores distintos (n1)
+    pub distinct_operators: usize,
+    /// Operandos distintos (n2)
+    pub distinct_operands: usize,
+    /// Total de operadores (N1)
+    pub total_operators: usize,
+    /// Total de operandos (N2)
+    pub total_operands: usize,
+    /// `(N1 + N2) * log2(n1 + n2)`
+    pub volume: f64,
+    /// `(n1 / 2) * (N2 / n2)`
+    pub difficulty: f64,
+    /// `dificuldade * volume`
+    pub effort: f64,
+}
+
+/// Por que um arquivo com histórico foi deixado de fora da análise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ExclusionReason {
+    /// `linguist-generated` no `.gitattributes`
+    LinguistGenerated,
+    /// `linguist-vendored` no `.gitattributes`
+    LinguistVendored,
+    /// `-diff` (ou `binary`) no `.gitattributes`
+    NoDiff,
+    /// Lockfile de gerenciador de pacotes (`Cargo.lock`, `package-lock.json`...)
+    Lockfile,
+    /// Cabeçalho de código gerado (`@generated`, `DO NOT EDIT`...)
+    GeneratedHeader,
+    /// Arquivo minificado
+    Minified,
+}
+
+impl fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ExclusionReason::LinguistGenerated => "linguist-generated",
+            ExclusionReason::LinguistVendored => "linguist-vendored",
+            ExclusionReason::NoDiff => "-diff",
+            ExclusionReason::Lockfile => "lockfile",
+            ExclusionReason::GeneratedHeader => "gerado",
+            ExclusionReason::Minified => "minificado",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Commit que passou dos limites de arquivos ou linhas alterados por commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MassChange {
+    pub commit: String,
+    /// Primeira linha da mensagem do commit.
+    pub summary: String,
+    pub files: usize,
+    pub lines: usize,
+}
+
+/// Histórico de mudanças de um arquivo binário, que não tem churn de linhas.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BinaryChurn {
+    pub path: PathBuf,
+    /// Número de commits que alteraram o arquivo.
+    pub changes: usize,
+    /// Variação total do tamanho, em bytes (negativa se o arquivo encolheu).
+    pub size_delta: i64,
+    /// Soma das variações de tamanho em valor absoluto, em bytes.
+    pub bytes_changed: u64,
+}